@@ -1,10 +1,17 @@
 use anyhow::{Context, Result, anyhow};
+use arrow::array::{ArrayRef, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
 use clap::Parser;
 use csv::{ReaderBuilder, StringRecord};
 use deunicode::deunicode;
+use fst::automaton::Levenshtein;
+use fst::{IntoStreamer, Set, Streamer};
 use lazy_static::lazy_static;
-use log::{LevelFilter, info};
+use log::{LevelFilter, info, warn};
+use parquet::arrow::ArrowWriter;
 use regex::Regex;
+use roaring::RoaringBitmap;
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Serialize;
 use simple_logger::SimpleLogger;
@@ -12,8 +19,130 @@ use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-const DEFAULT_OUTPUT_FILENAMES: [(&str, &str); 6] = [
+/// Opt-in OpenTelemetry metrics and tracing for the streaming pass, enabled
+/// only by `--otlp-endpoint`. When the flag is absent, `main` never calls
+/// into this module, so no exporter, batch thread, or tonic channel is ever
+/// created.
+mod otel {
+    use anyhow::{Context, Result};
+    use opentelemetry::global;
+    use opentelemetry::metrics::Meter;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    const SERVICE_NAME: &str = "affiliation-aggregator";
+
+    /// The instruments `run` records into over the course of a pass, plus
+    /// the provider handles that keep their OTLP export pipelines alive.
+    /// Dropping this flushes and shuts both providers down.
+    pub struct Metrics {
+        pub rows_processed: opentelemetry::metrics::Counter<u64>,
+        pub with_ror: opentelemetry::metrics::Counter<u64>,
+        pub without_ror: opentelemetry::metrics::Counter<u64>,
+        pub affiliation_map_size: opentelemetry::metrics::UpDownCounter<i64>,
+        pub identifier_map_size: opentelemetry::metrics::UpDownCounter<i64>,
+        pub flush_batch_size: opentelemetry::metrics::Histogram<u64>,
+        last_affiliation_map_size: AtomicI64,
+        last_identifier_map_size: AtomicI64,
+        tracer_provider: SdkTracerProvider,
+        meter_provider: SdkMeterProvider,
+    }
+
+    impl Metrics {
+        /// Records the current distinct-affiliation and distinct-identifier
+        /// map sizes; called alongside the existing `--log-every` cadence
+        /// rather than per row, since re-reading `HashMap::len()` per row
+        /// would dwarf the cost of the row processing itself.
+        pub fn record_map_sizes(&self, affiliations: i64, identifiers: i64) {
+            let prev_affiliations = self.last_affiliation_map_size.swap(affiliations, Ordering::Relaxed);
+            let prev_identifiers = self.last_identifier_map_size.swap(identifiers, Ordering::Relaxed);
+            self.affiliation_map_size.add(affiliations - prev_affiliations, &[]);
+            self.identifier_map_size.add(identifiers - prev_identifiers, &[]);
+        }
+    }
+
+    impl Drop for Metrics {
+        fn drop(&mut self) {
+            let _ = self.tracer_provider.shutdown();
+            let _ = self.meter_provider.shutdown();
+        }
+    }
+
+    /// Initializes OTLP tracer and meter providers against `endpoint`,
+    /// bridges the existing `log` macros into `tracing` (so `info!`/`warn!`
+    /// call sites are unchanged) and installs a `tracing` subscriber that
+    /// exports spans through the tracer, and registers the instruments this
+    /// run records into. Replaces `SimpleLogger` as the logging backend for
+    /// the duration of the process.
+    pub fn init(endpoint: &str, log_level: log::LevelFilter) -> Result<Metrics> {
+        let resource = Resource::builder().with_service_name(SERVICE_NAME).build();
+
+        let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .context("Build OTLP span exporter")?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource.clone())
+            .build();
+        global::set_tracer_provider(tracer_provider.clone());
+
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .context("Build OTLP metric exporter")?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter)
+            .with_resource(resource)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        tracing_log::LogTracer::init().context("Bridge `log` macros into `tracing`")?;
+        let tracer = opentelemetry::trace::TracerProvider::tracer(&tracer_provider, SERVICE_NAME);
+        tracing_subscriber::registry()
+            .with(tracing_level_filter(log_level))
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .try_init()
+            .context("Install tracing subscriber")?;
+
+        let meter: Meter = global::meter(SERVICE_NAME);
+        Ok(Metrics {
+            rows_processed: meter.u64_counter("rows_processed").build(),
+            with_ror: meter.u64_counter("affiliation_entries_with_ror").build(),
+            without_ror: meter.u64_counter("affiliation_entries_without_ror").build(),
+            affiliation_map_size: meter.i64_up_down_counter("distinct_affiliation_map_size").build(),
+            identifier_map_size: meter.i64_up_down_counter("distinct_identifier_map_size").build(),
+            flush_batch_size: meter.u64_histogram("flush_pending_batch_size").build(),
+            last_affiliation_map_size: AtomicI64::new(0),
+            last_identifier_map_size: AtomicI64::new(0),
+            tracer_provider,
+            meter_provider,
+        })
+    }
+
+    /// Mirrors the CLI's `--log-level` through to the `tracing` subscriber,
+    /// the same way `SimpleLogger` does for the non-OTLP path.
+    fn tracing_level_filter(level: log::LevelFilter) -> tracing_subscriber::filter::LevelFilter {
+        match level {
+            log::LevelFilter::Off => tracing_subscriber::filter::LevelFilter::OFF,
+            log::LevelFilter::Error => tracing_subscriber::filter::LevelFilter::ERROR,
+            log::LevelFilter::Warn => tracing_subscriber::filter::LevelFilter::WARN,
+            log::LevelFilter::Info => tracing_subscriber::filter::LevelFilter::INFO,
+            log::LevelFilter::Debug => tracing_subscriber::filter::LevelFilter::DEBUG,
+            log::LevelFilter::Trace => tracing_subscriber::filter::LevelFilter::TRACE,
+        }
+    }
+}
+
+const DEFAULT_OUTPUT_FILENAMES: [(&str, &str); 7] = [
     ("with_ror", "affiliations_with_ror.json"),
     ("without_ror", "affiliations_without_ror.json"),
     ("overlap", "affiliation_overlap.json"),
@@ -23,6 +152,7 @@ const DEFAULT_OUTPUT_FILENAMES: [(&str, &str); 6] = [
         "normalized_affiliation_doi_distribution.json",
     ),
     ("identifier_dois", "ror_identifier_doi_distribution.json"),
+    ("suggested_ror", "suggested_ror.json"),
 ];
 
 #[derive(Parser, Debug)]
@@ -71,6 +201,29 @@ struct Cli {
     )]
     identifier_doi_output: Option<PathBuf>,
 
+    #[arg(long, help = "Override output path for suggested ROR assignments")]
+    suggested_ror_output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Suggest ROR IDs for unassigned affiliations by fuzzy-matching against assigned ones"
+    )]
+    suggest_ror: bool,
+
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Maximum Levenshtein edit distance allowed when --suggest-ror is set"
+    )]
+    fuzzy_max_edits: u32,
+
+    #[arg(
+        long,
+        default_value_t = 0.6,
+        help = "Minimum confidence (0.0-1.0) required to keep a --suggest-ror suggestion"
+    )]
+    fuzzy_min_confidence: f64,
+
     #[arg(
         long,
         default_value_t = LevelFilter::Info,
@@ -85,6 +238,140 @@ struct Cli {
         help = "Log progress every N rows processed"
     )]
     log_every: u64,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Json,
+        help = "Encoding for all aggregate output files"
+    )]
+    format: OutputFormat,
+
+    #[arg(
+        long,
+        help = "OTLP gRPC endpoint (e.g. http://localhost:4317); when set, replaces the plain logger with OpenTelemetry tracing and emits rows-processed/with-ror/without-ror counters, affiliation/identifier map size gauges, and a flush-batch-size histogram. Dependencies stay dormant when unset."
+    )]
+    otlp_endpoint: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = NormalizeMode::Basic,
+        help = "Normalization applied before grouping into a distribution bucket: basic (deunicode/lowercase/strip only, the historical behavior), tokens (also drops stopwords), tokens-sorted (tokens, plus sorts them so word order is ignored)"
+    )]
+    normalize_mode: NormalizeMode,
+
+    #[arg(
+        long,
+        help = "Extra stopwords (one per line) to drop in --normalize-mode tokens/tokens-sorted, added to the built-in list"
+    )]
+    stopwords: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DoiStorage::Strings,
+        help = "How DOIs are held per bucket while streaming: strings (one heap allocation per DOI, historical behavior) or bitmap (DOIs interned to u32 ids and packed into a RoaringBitmap, far lower memory on full-corpus runs)"
+    )]
+    doi_storage: DoiStorage,
+
+    #[arg(
+        long,
+        help = "Omit the `dois` array from DOI distribution outputs, keeping only `unique_dois`; avoids materializing the full sorted DOI list for each bucket"
+    )]
+    doi_summary_only: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Parquet,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum NormalizeMode {
+    Basic,
+    Tokens,
+    TokensSorted,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DoiStorage {
+    Strings,
+    Bitmap,
+}
+
+impl Default for DoiStorage {
+    fn default() -> Self {
+        DoiStorage::Strings
+    }
+}
+
+/// Assigns each distinct DOI a sequential `u32` id so `DoiStorage::Bitmap`
+/// can track membership in a `RoaringBitmap` instead of a `FxHashSet<String>`.
+#[derive(Default)]
+struct DoiInterner {
+    ids: FxHashMap<String, u32>,
+    strings: Vec<String>,
+}
+
+impl DoiInterner {
+    fn intern(&mut self, doi: &str) -> u32 {
+        if let Some(id) = self.ids.get(doi) {
+            return *id;
+        }
+        let id = self.strings.len() as u32;
+        self.strings.push(doi.to_string());
+        self.ids.insert(doi.to_string(), id);
+        id
+    }
+
+    fn resolve(&self, id: u32) -> &str {
+        &self.strings[id as usize]
+    }
+}
+
+/// Per-bucket DOI membership, backed by either a plain string set or a
+/// `RoaringBitmap` over interned ids depending on `--doi-storage`.
+enum DoiSet {
+    Strings(FxHashSet<String>),
+    Bitmap(RoaringBitmap),
+}
+
+impl DoiSet {
+    fn new(storage: DoiStorage) -> Self {
+        match storage {
+            DoiStorage::Strings => DoiSet::Strings(FxHashSet::default()),
+            DoiStorage::Bitmap => DoiSet::Bitmap(RoaringBitmap::new()),
+        }
+    }
+
+    fn insert(&mut self, doi: &str, interner: &mut DoiInterner) {
+        match self {
+            DoiSet::Strings(set) => {
+                set.insert(doi.to_string());
+            }
+            DoiSet::Bitmap(bitmap) => {
+                bitmap.insert(interner.intern(doi));
+            }
+        }
+    }
+
+    fn len(&self) -> u64 {
+        match self {
+            DoiSet::Strings(set) => set.len() as u64,
+            DoiSet::Bitmap(bitmap) => bitmap.len(),
+        }
+    }
+
+    fn sorted_dois(&self, interner: &DoiInterner) -> Vec<String> {
+        let mut dois: Vec<String> = match self {
+            DoiSet::Strings(set) => set.iter().cloned().collect(),
+            DoiSet::Bitmap(bitmap) => bitmap.iter().map(|id| interner.resolve(id).to_string()).collect(),
+        };
+        dois.sort();
+        dois
+    }
 }
 
 fn parse_level(input: &str) -> std::result::Result<LevelFilter, String> {
@@ -114,31 +401,61 @@ struct AffiliationCounts {
     client_counts: FxHashMap<String, u64>,
 }
 
-#[derive(Default)]
 struct NormalizedCounts {
     total: u64,
     affiliations: FxHashMap<String, u64>,
-    dois: FxHashSet<String>,
+    dois: DoiSet,
     provider_counts: FxHashMap<String, u64>,
     client_counts: FxHashMap<String, u64>,
 }
 
-#[derive(Default)]
+impl NormalizedCounts {
+    fn new(doi_storage: DoiStorage) -> Self {
+        Self {
+            total: 0,
+            affiliations: FxHashMap::default(),
+            dois: DoiSet::new(doi_storage),
+            provider_counts: FxHashMap::default(),
+            client_counts: FxHashMap::default(),
+        }
+    }
+}
+
 struct IdentifierCounts {
     total: u64,
-    dois: FxHashSet<String>,
+    dois: DoiSet,
     provider_counts: FxHashMap<String, u64>,
     client_counts: FxHashMap<String, u64>,
 }
 
+impl IdentifierCounts {
+    fn new(doi_storage: DoiStorage) -> Self {
+        Self {
+            total: 0,
+            dois: DoiSet::new(doi_storage),
+            provider_counts: FxHashMap::default(),
+            client_counts: FxHashMap::default(),
+        }
+    }
+}
+
 #[derive(Default)]
 struct Aggregator {
     affiliations: FxHashMap<String, AffiliationCounts>,
     normalized: FxHashMap<String, NormalizedCounts>,
     identifiers: FxHashMap<String, IdentifierCounts>,
+    doi_storage: DoiStorage,
+    interner: DoiInterner,
 }
 
 impl Aggregator {
+    fn new(doi_storage: DoiStorage) -> Self {
+        Self {
+            doi_storage,
+            ..Default::default()
+        }
+    }
+
     fn add_entry(
         &mut self,
         doi: &str,
@@ -148,6 +465,7 @@ impl Aggregator {
         provider_id: Option<&str>,
         client_id: Option<&str>,
     ) {
+        let doi_storage = self.doi_storage;
         let entry = self
             .affiliations
             .entry(affiliation.to_string())
@@ -155,9 +473,12 @@ impl Aggregator {
         if let Some(ror) = ror_id {
             entry.with_ror += 1;
             *entry.ror_counts.entry(ror.to_string()).or_insert(0) += 1;
-            let identifier_entry = self.identifiers.entry(ror.to_string()).or_default();
+            let identifier_entry = self
+                .identifiers
+                .entry(ror.to_string())
+                .or_insert_with(|| IdentifierCounts::new(doi_storage));
             identifier_entry.total += 1;
-            identifier_entry.dois.insert(doi.to_string());
+            identifier_entry.dois.insert(doi, &mut self.interner);
             if let Some(pid) = provider_id.and_then(non_empty_str) {
                 *identifier_entry
                     .provider_counts
@@ -180,13 +501,16 @@ impl Aggregator {
             *entry.client_counts.entry(cid.to_string()).or_insert(0) += 1;
         }
         if let Some(norm) = normalized {
-            let normalized_entry = self.normalized.entry(norm.to_string()).or_default();
+            let normalized_entry = self
+                .normalized
+                .entry(norm.to_string())
+                .or_insert_with(|| NormalizedCounts::new(doi_storage));
             normalized_entry.total += 1;
             *normalized_entry
                 .affiliations
                 .entry(affiliation.to_string())
                 .or_insert(0) += 1;
-            normalized_entry.dois.insert(doi.to_string());
+            normalized_entry.dois.insert(doi, &mut self.interner);
             if let Some(pid) = provider_id.and_then(non_empty_str) {
                 *normalized_entry
                     .provider_counts
@@ -285,20 +609,20 @@ impl Aggregator {
         items
     }
 
-    fn normalized_affiliation_doi_records(&self) -> Vec<NormalizedAffiliationDoisRecord> {
+    fn normalized_affiliation_doi_records(
+        &self,
+        doi_summary_only: bool,
+    ) -> Vec<NormalizedAffiliationDoisRecord> {
         let mut items: Vec<_> = self
             .normalized
             .iter()
-            .map(|(norm, stats)| {
-                let mut dois: Vec<_> = stats.dois.iter().cloned().collect();
-                dois.sort();
-                NormalizedAffiliationDoisRecord {
-                    normalized: norm.clone(),
-                    occurrences: stats.total,
-                    dois,
-                    providers: entity_breakdown(&stats.provider_counts),
-                    clients: entity_breakdown(&stats.client_counts),
-                }
+            .map(|(norm, stats)| NormalizedAffiliationDoisRecord {
+                normalized: norm.clone(),
+                occurrences: stats.total,
+                unique_dois: doi_summary_only.then(|| stats.dois.len()),
+                dois: (!doi_summary_only).then(|| stats.dois.sorted_dois(&self.interner)),
+                providers: entity_breakdown(&stats.provider_counts),
+                clients: entity_breakdown(&stats.client_counts),
             })
             .collect();
         items.sort_by(|a, b| {
@@ -309,20 +633,17 @@ impl Aggregator {
         items
     }
 
-    fn identifier_doi_records(&self) -> Vec<IdentifierDoisRecord> {
+    fn identifier_doi_records(&self, doi_summary_only: bool) -> Vec<IdentifierDoisRecord> {
         let mut items: Vec<_> = self
             .identifiers
             .iter()
-            .map(|(identifier, stats)| {
-                let mut dois: Vec<_> = stats.dois.iter().cloned().collect();
-                dois.sort();
-                IdentifierDoisRecord {
-                    identifier: identifier.clone(),
-                    occurrences: stats.total,
-                    dois,
-                    providers: entity_breakdown(&stats.provider_counts),
-                    clients: entity_breakdown(&stats.client_counts),
-                }
+            .map(|(identifier, stats)| IdentifierDoisRecord {
+                identifier: identifier.clone(),
+                occurrences: stats.total,
+                unique_dois: doi_summary_only.then(|| stats.dois.len()),
+                dois: (!doi_summary_only).then(|| stats.dois.sorted_dois(&self.interner)),
+                providers: entity_breakdown(&stats.provider_counts),
+                clients: entity_breakdown(&stats.client_counts),
             })
             .collect();
         items.sort_by(|a, b| {
@@ -380,7 +701,13 @@ struct AffiliationCount {
 struct NormalizedAffiliationDoisRecord {
     normalized: String,
     occurrences: u64,
-    dois: Vec<String>,
+    /// Only present under `--doi-summary-only`, where it stands in for the
+    /// omitted `dois` array; a default run keeps the pre-existing schema
+    /// (just `dois`, no `unique_dois`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unique_dois: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dois: Option<Vec<String>>,
     providers: EntityBreakdown,
     clients: EntityBreakdown,
 }
@@ -389,7 +716,13 @@ struct NormalizedAffiliationDoisRecord {
 struct IdentifierDoisRecord {
     identifier: String,
     occurrences: u64,
-    dois: Vec<String>,
+    /// Only present under `--doi-summary-only`, where it stands in for the
+    /// omitted `dois` array; a default run keeps the pre-existing schema
+    /// (just `dois`, no `unique_dois`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unique_dois: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dois: Option<Vec<String>>,
     providers: EntityBreakdown,
     clients: EntityBreakdown,
 }
@@ -400,6 +733,16 @@ struct EntityBreakdown {
     counts: BTreeMap<String, u64>,
 }
 
+#[derive(Serialize)]
+struct SuggestedRorRecord {
+    affiliation: String,
+    normalized: String,
+    suggested_ror: String,
+    matched_affiliation: String,
+    edit_distance: u32,
+    confidence: f64,
+}
+
 fn to_btree(map: &FxHashMap<String, u64>) -> BTreeMap<String, u64> {
     map.iter().map(|(k, v)| (k.clone(), *v)).collect()
 }
@@ -470,14 +813,23 @@ impl ColumnIndices {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    SimpleLogger::new()
-        .with_level(cli.log_level)
-        .init()
-        .context("Initialize logger")?;
-    run(cli)
+    let metrics = match &cli.otlp_endpoint {
+        Some(endpoint) => Some(otel::init(endpoint, cli.log_level)?),
+        None => {
+            SimpleLogger::new()
+                .with_level(cli.log_level)
+                .init()
+                .context("Initialize logger")?;
+            None
+        }
+    };
+    run(cli, metrics.as_ref())
 }
 
-fn run(cli: Cli) -> Result<()> {
+fn run(cli: Cli, metrics: Option<&otel::Metrics>) -> Result<()> {
+    let root_span = tracing::info_span!("run");
+    let _root_guard = root_span.enter();
+
     let mut reader = ReaderBuilder::new()
         .has_headers(true)
         .from_path(&cli.input)
@@ -487,56 +839,73 @@ fn run(cli: Cli) -> Result<()> {
         .context("Unable to read CSV headers")?
         .clone();
     let indices = ColumnIndices::from_headers(&headers)?;
+    let normalize_config = NormalizeConfig::from_cli(&cli)?;
 
-    let mut aggregator = Aggregator::default();
+    let mut aggregator = Aggregator::new(cli.doi_storage);
     let mut current_doi: Option<String> = None;
     let mut current_provider: Option<String> = None;
     let mut current_client: Option<String> = None;
     let mut pending: FxHashMap<(String, String), PendingAffiliation> = FxHashMap::default();
     let mut processed_rows: u64 = 0;
 
-    for record in reader.records() {
-        let record = record?;
-        processed_rows += 1;
-        if cli.log_every > 0 && processed_rows % cli.log_every == 0 {
-            info!("Processed {} rows", processed_rows);
-        }
-        let doi = record.get(indices.doi).unwrap_or("").trim();
-        if doi.is_empty() {
-            continue;
-        }
-        let provider_value = record.get(indices.provider_id).unwrap_or("").trim();
-        let client_value = record.get(indices.client_id).unwrap_or("").trim();
-        if current_doi.as_deref() != Some(doi) {
-            if let Some(prev) = &current_doi {
-                flush_pending(
-                    prev,
-                    current_provider.as_deref(),
-                    current_client.as_deref(),
-                    &mut pending,
-                    &mut aggregator,
-                );
+    {
+        let _read_guard = tracing::info_span!("read_loop").entered();
+        for record in reader.records() {
+            let record = record?;
+            processed_rows += 1;
+            if let Some(m) = metrics {
+                m.rows_processed.add(1, &[]);
             }
-            current_doi = Some(doi.to_string());
-            current_provider = None;
-            current_client = None;
-        }
-        if !provider_value.is_empty() {
-            current_provider = Some(provider_value.to_string());
+            if cli.log_every > 0 && processed_rows % cli.log_every == 0 {
+                info!("Processed {} rows", processed_rows);
+                if let Some(m) = metrics {
+                    m.record_map_sizes(
+                        aggregator.affiliations.len() as i64,
+                        aggregator.identifiers.len() as i64,
+                    );
+                }
+            }
+            let doi = record.get(indices.doi).unwrap_or("").trim();
+            if doi.is_empty() {
+                continue;
+            }
+            let provider_value = record.get(indices.provider_id).unwrap_or("").trim();
+            let client_value = record.get(indices.client_id).unwrap_or("").trim();
+            if current_doi.as_deref() != Some(doi) {
+                if let Some(prev) = &current_doi {
+                    flush_pending(
+                        prev,
+                        current_provider.as_deref(),
+                        current_client.as_deref(),
+                        &mut pending,
+                        &mut aggregator,
+                        metrics,
+                        &normalize_config,
+                    );
+                }
+                current_doi = Some(doi.to_string());
+                current_provider = None;
+                current_client = None;
+            }
+            if !provider_value.is_empty() {
+                current_provider = Some(provider_value.to_string());
+            }
+            if !client_value.is_empty() {
+                current_client = Some(client_value.to_string());
+            }
+            handle_record(&record, &indices, &mut pending);
         }
-        if !client_value.is_empty() {
-            current_client = Some(client_value.to_string());
+        if let Some(last_doi) = &current_doi {
+            flush_pending(
+                last_doi,
+                current_provider.as_deref(),
+                current_client.as_deref(),
+                &mut pending,
+                &mut aggregator,
+                metrics,
+                &normalize_config,
+            );
         }
-        handle_record(&record, &indices, &mut pending);
-    }
-    if let Some(last_doi) = &current_doi {
-        flush_pending(
-            last_doi,
-            current_provider.as_deref(),
-            current_client.as_deref(),
-            &mut pending,
-            &mut aggregator,
-        );
     }
 
     let outputs = determine_output_paths(&cli);
@@ -551,44 +920,70 @@ fn run(cli: Cli) -> Result<()> {
     let without_ror = aggregator.without_ror_records();
     let overlap = aggregator.overlap_records();
     let distribution = aggregator.distribution_records();
-    let normalized_affiliation_dois = aggregator.normalized_affiliation_doi_records();
-    let identifier_dois = aggregator.identifier_doi_records();
+    let normalized_affiliation_dois =
+        aggregator.normalized_affiliation_doi_records(cli.doi_summary_only);
+    let identifier_dois = aggregator.identifier_doi_records(cli.doi_summary_only);
+
+    let _write_guard = tracing::info_span!("write_json_records").entered();
 
-    write_json_records(
+    write_output(
         outputs
             .get("with_ror")
             .expect("missing with_ror output path"),
         &with_ror,
+        cli.format,
     )?;
-    write_json_records(
+    write_output(
         outputs
             .get("without_ror")
             .expect("missing without_ror output path"),
         &without_ror,
+        cli.format,
     )?;
-    write_json_records(
+    write_output(
         outputs.get("overlap").expect("missing overlap output path"),
         &overlap,
+        cli.format,
     )?;
-    write_json_records(
+    write_output(
         outputs
             .get("distribution")
             .expect("missing distribution output path"),
         &distribution,
+        cli.format,
     )?;
-    write_json_records(
+    write_output(
         outputs
             .get("normalized_affiliation_dois")
             .expect("missing normalized affiliation doi output"),
         &normalized_affiliation_dois,
+        cli.format,
     )?;
-    write_json_records(
+    write_output(
         outputs
             .get("identifier_dois")
             .expect("missing identifier doi output"),
         &identifier_dois,
+        cli.format,
     )?;
 
+    if cli.suggest_ror {
+        let suggested_ror =
+            suggest_ror_assignments(&with_ror, &without_ror, cli.fuzzy_max_edits, cli.fuzzy_min_confidence)?;
+        info!(
+            "Suggested ROR IDs for {} of {} unassigned affiliations",
+            suggested_ror.len(),
+            without_ror.len()
+        );
+        write_output(
+            outputs
+                .get("suggested_ror")
+                .expect("missing suggested ror output path"),
+            &suggested_ror,
+            cli.format,
+        )?;
+    }
+
     info!("Finished. Processed {} rows", processed_rows);
     Ok(())
 }
@@ -608,14 +1003,22 @@ fn determine_output_paths(cli: &Cli) -> FxHashMap<String, PathBuf> {
             "distribution" => cli.distribution_output.clone(),
             "normalized_affiliation_dois" => cli.normalized_doi_output.clone(),
             "identifier_dois" => cli.identifier_doi_output.clone(),
+            "suggested_ror" => cli.suggested_ror_output.clone(),
             _ => None,
         };
-        let path = custom.unwrap_or_else(|| base_dir.join(filename));
+        let path = custom.unwrap_or_else(|| base_dir.join(default_filename(filename, cli.format)));
         map.insert(key.to_string(), path);
     }
     map
 }
 
+fn default_filename(filename: &str, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => filename.to_string(),
+        OutputFormat::Parquet => filename.replace(".json", ".parquet"),
+    }
+}
+
 fn write_json_records<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
     let file = File::create(path).with_context(|| format!("Open {}", path.display()))?;
     let mut writer = BufWriter::new(file);
@@ -625,6 +1028,443 @@ fn write_json_records<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
     Ok(())
 }
 
+/// Writes a record set as either pretty-printed JSON or a Parquet file built
+/// from a single record batch, per `--format`. The Parquet side explodes the repeated
+/// `dois`/`affiliations`/`*_counts` fields into list columns rather than
+/// inlining them as JSON text, which is what makes the format worth offering.
+fn write_output<T: Serialize + ToColumnar>(
+    path: &Path,
+    records: &[T],
+    format: OutputFormat,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => write_json_records(path, records),
+        OutputFormat::Parquet => {
+            let (schema, batch) = T::to_record_batch(records)?;
+            write_parquet_batch(path, schema, batch)
+        }
+    }
+}
+
+fn write_parquet_batch(path: &Path, schema: Arc<Schema>, batch: RecordBatch) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Open {}", path.display()))?;
+    let mut writer =
+        ArrowWriter::try_new(file, schema, None).context("Create Arrow/Parquet writer")?;
+    writer.write(&batch).context("Write Parquet record batch")?;
+    writer.close().context("Close Parquet writer")?;
+    Ok(())
+}
+
+/// Converts a slice of one of the aggregate record types into a single Arrow
+/// `RecordBatch`, one row per element, for `--format parquet`.
+trait ToColumnar: Sized {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)>;
+}
+
+fn breakdown_fields(prefix: &str) -> Vec<Field> {
+    let item = |name: &str, data_type: DataType| Arc::new(Field::new(name, data_type, true));
+    vec![
+        Field::new(format!("{prefix}_unique_total"), DataType::UInt64, false),
+        Field::new(
+            format!("{prefix}_keys"),
+            DataType::List(item("item", DataType::Utf8)),
+            true,
+        ),
+        Field::new(
+            format!("{prefix}_values"),
+            DataType::List(item("item", DataType::UInt64)),
+            true,
+        ),
+    ]
+}
+
+fn breakdown_columns(breakdowns: &[&EntityBreakdown]) -> (ArrayRef, ArrayRef, ArrayRef) {
+    let unique_total: ArrayRef = Arc::new(UInt64Array::from_iter_values(
+        breakdowns.iter().map(|b| b.unique_total),
+    ));
+    let keys = string_list_column(
+        breakdowns
+            .iter()
+            .map(|b| b.counts.keys().cloned().collect::<Vec<_>>()),
+    );
+    let values = u64_list_column(
+        breakdowns
+            .iter()
+            .map(|b| b.counts.values().copied().collect::<Vec<_>>()),
+    );
+    (unique_total, keys, values)
+}
+
+fn string_list_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+        true,
+    )
+}
+
+fn u64_list_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::List(Arc::new(Field::new("item", DataType::UInt64, true))),
+        true,
+    )
+}
+
+fn string_list_column(rows: impl Iterator<Item = Vec<String>>) -> ArrayRef {
+    use arrow::array::{ListBuilder, StringBuilder};
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for row in rows {
+        for value in row {
+            builder.values().append_value(value);
+        }
+        builder.append(true);
+    }
+    Arc::new(builder.finish())
+}
+
+fn u64_list_column(rows: impl Iterator<Item = Vec<u64>>) -> ArrayRef {
+    use arrow::array::{ListBuilder, UInt64Builder};
+    let mut builder = ListBuilder::new(UInt64Builder::new());
+    for row in rows {
+        for value in row {
+            builder.values().append_value(value);
+        }
+        builder.append(true);
+    }
+    Arc::new(builder.finish())
+}
+
+impl ToColumnar for AffiliationWithRorRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let affiliation: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.affiliation.as_str()),
+        ));
+        let occurrences: ArrayRef =
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.occurrences)));
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+        let ror_keys = string_list_column(
+            records
+                .iter()
+                .map(|r| r.ror_assignments.keys().cloned().collect()),
+        );
+        let ror_values = u64_list_column(
+            records
+                .iter()
+                .map(|r| r.ror_assignments.values().copied().collect()),
+        );
+
+        let mut fields = vec![
+            Field::new("affiliation", DataType::Utf8, false),
+            Field::new("occurrences", DataType::UInt64, false),
+        ];
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+        fields.push(string_list_field("ror_assignment_keys"));
+        fields.push(u64_list_field("ror_assignment_values"));
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                affiliation,
+                occurrences,
+                providers_unique,
+                providers_keys,
+                providers_values,
+                clients_unique,
+                clients_keys,
+                clients_values,
+                ror_keys,
+                ror_values,
+            ],
+        )?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for AffiliationWithoutRorRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let affiliation: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.affiliation.as_str()),
+        ));
+        let occurrences: ArrayRef =
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.occurrences)));
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+
+        let mut fields = vec![
+            Field::new("affiliation", DataType::Utf8, false),
+            Field::new("occurrences", DataType::UInt64, false),
+        ];
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                affiliation,
+                occurrences,
+                providers_unique,
+                providers_keys,
+                providers_values,
+                clients_unique,
+                clients_keys,
+                clients_values,
+            ],
+        )?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for AffiliationOverlapRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let affiliation: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.affiliation.as_str()),
+        ));
+        let unassigned_occurrences: ArrayRef = Arc::new(UInt64Array::from_iter_values(
+            records.iter().map(|r| r.unassigned_occurrences),
+        ));
+        let assigned_occurrences: ArrayRef = Arc::new(UInt64Array::from_iter_values(
+            records.iter().map(|r| r.assigned_occurrences),
+        ));
+        let identifier_keys = string_list_column(
+            records
+                .iter()
+                .map(|r| r.identifier_occurrences.keys().cloned().collect()),
+        );
+        let identifier_values = u64_list_column(
+            records
+                .iter()
+                .map(|r| r.identifier_occurrences.values().copied().collect()),
+        );
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+
+        let mut fields = vec![
+            Field::new("affiliation", DataType::Utf8, false),
+            Field::new("unassigned_occurrences", DataType::UInt64, false),
+            Field::new("assigned_occurrences", DataType::UInt64, false),
+            string_list_field("identifier_occurrence_keys"),
+            u64_list_field("identifier_occurrence_values"),
+        ];
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                affiliation,
+                unassigned_occurrences,
+                assigned_occurrences,
+                identifier_keys,
+                identifier_values,
+                providers_unique,
+                providers_keys,
+                providers_values,
+                clients_unique,
+                clients_keys,
+                clients_values,
+            ],
+        )?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for NormalizedDistributionRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let normalized: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.normalized.as_str()),
+        ));
+        let total_count: ArrayRef =
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.total_count)));
+        let affiliation_names = string_list_column(records.iter().map(|r| {
+            r.affiliations
+                .iter()
+                .map(|a| a.affiliation.clone())
+                .collect()
+        }));
+        let affiliation_counts = u64_list_column(
+            records
+                .iter()
+                .map(|r| r.affiliations.iter().map(|a| a.occurrences).collect()),
+        );
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+
+        let mut fields = vec![
+            Field::new("normalized", DataType::Utf8, false),
+            Field::new("total_count", DataType::UInt64, false),
+            string_list_field("affiliation_names"),
+            u64_list_field("affiliation_occurrences"),
+        ];
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                normalized,
+                total_count,
+                affiliation_names,
+                affiliation_counts,
+                providers_unique,
+                providers_keys,
+                providers_values,
+                clients_unique,
+                clients_keys,
+                clients_values,
+            ],
+        )?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for NormalizedAffiliationDoisRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let normalized: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.normalized.as_str()),
+        ));
+        let occurrences: ArrayRef =
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.occurrences)));
+        // `--doi-summary-only` is a whole-run flag, so every record agrees on
+        // whether `unique_dois` is present; an empty `records` keeps the
+        // pre-existing (no `unique_dois` column) schema.
+        let has_unique_dois = records.first().is_some_and(|r| r.unique_dois.is_some());
+        let unique_dois: Option<ArrayRef> = has_unique_dois.then(|| {
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.unique_dois.unwrap_or(0)))) as ArrayRef
+        });
+        // Under `--doi-summary-only` the `dois` column is omitted, as in JSON.
+        let dois: Option<ArrayRef> = (!has_unique_dois).then(|| {
+            string_list_column(records.iter().map(|r| r.dois.clone().unwrap_or_default()))
+        });
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+
+        let mut fields = vec![
+            Field::new("normalized", DataType::Utf8, false),
+            Field::new("occurrences", DataType::UInt64, false),
+        ];
+        if has_unique_dois {
+            fields.push(Field::new("unique_dois", DataType::UInt64, false));
+        } else {
+            fields.push(string_list_field("dois"));
+        }
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+
+        let mut columns = vec![normalized, occurrences];
+        columns.extend(unique_dois);
+        columns.extend(dois);
+        columns.extend([providers_unique, providers_keys, providers_values, clients_unique, clients_keys, clients_values]);
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), columns)?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for IdentifierDoisRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let identifier: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.identifier.as_str()),
+        ));
+        let occurrences: ArrayRef =
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.occurrences)));
+        let has_unique_dois = records.first().is_some_and(|r| r.unique_dois.is_some());
+        let unique_dois: Option<ArrayRef> = has_unique_dois.then(|| {
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.unique_dois.unwrap_or(0)))) as ArrayRef
+        });
+        // Under `--doi-summary-only` the `dois` column is omitted, as in JSON.
+        let dois: Option<ArrayRef> = (!has_unique_dois).then(|| {
+            string_list_column(records.iter().map(|r| r.dois.clone().unwrap_or_default()))
+        });
+        let providers = records.iter().map(|r| &r.providers).collect::<Vec<_>>();
+        let clients = records.iter().map(|r| &r.clients).collect::<Vec<_>>();
+        let (providers_unique, providers_keys, providers_values) = breakdown_columns(&providers);
+        let (clients_unique, clients_keys, clients_values) = breakdown_columns(&clients);
+
+        let mut fields = vec![
+            Field::new("identifier", DataType::Utf8, false),
+            Field::new("occurrences", DataType::UInt64, false),
+        ];
+        if has_unique_dois {
+            fields.push(Field::new("unique_dois", DataType::UInt64, false));
+        } else {
+            fields.push(string_list_field("dois"));
+        }
+        fields.extend(breakdown_fields("providers"));
+        fields.extend(breakdown_fields("clients"));
+
+        let mut columns = vec![identifier, occurrences];
+        columns.extend(unique_dois);
+        columns.extend(dois);
+        columns.extend([providers_unique, providers_keys, providers_values, clients_unique, clients_keys, clients_values]);
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), columns)?;
+        Ok((schema, batch))
+    }
+}
+
+impl ToColumnar for SuggestedRorRecord {
+    fn to_record_batch(records: &[Self]) -> Result<(Arc<Schema>, RecordBatch)> {
+        let affiliation: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.affiliation.as_str()),
+        ));
+        let normalized: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.normalized.as_str()),
+        ));
+        let suggested_ror: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.suggested_ror.as_str()),
+        ));
+        let matched_affiliation: ArrayRef = Arc::new(StringArray::from_iter_values(
+            records.iter().map(|r| r.matched_affiliation.as_str()),
+        ));
+        let edit_distance: ArrayRef = Arc::new(arrow::array::UInt32Array::from_iter_values(
+            records.iter().map(|r| r.edit_distance),
+        ));
+        let confidence: ArrayRef = Arc::new(arrow::array::Float64Array::from_iter_values(
+            records.iter().map(|r| r.confidence),
+        ));
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("affiliation", DataType::Utf8, false),
+            Field::new("normalized", DataType::Utf8, false),
+            Field::new("suggested_ror", DataType::Utf8, false),
+            Field::new("matched_affiliation", DataType::Utf8, false),
+            Field::new("edit_distance", DataType::UInt32, false),
+            Field::new("confidence", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                affiliation,
+                normalized,
+                suggested_ror,
+                matched_affiliation,
+                edit_distance,
+                confidence,
+            ],
+        )?;
+        Ok((schema, batch))
+    }
+}
+
 fn handle_record(
     record: &StringRecord,
     idx: &ColumnIndices,
@@ -659,14 +1499,17 @@ fn flush_pending(
     client_id: Option<&str>,
     pending: &mut FxHashMap<(String, String), PendingAffiliation>,
     aggregator: &mut Aggregator,
+    metrics: Option<&otel::Metrics>,
+    normalize_config: &NormalizeConfig,
 ) {
+    let mut batch_size: u64 = 0;
     for pending_aff in pending.values() {
         if let Some(name) = pending_aff
             .name
             .as_deref()
             .and_then(sanitize_affiliation_value)
         {
-            let normalized_owned = normalize_text(name);
+            let normalized_owned = normalize_text(name, normalize_config);
             let normalized = normalized_owned.as_deref();
             let ror_owned = normalize_ror_identifier(
                 pending_aff.identifier.as_deref(),
@@ -674,8 +1517,19 @@ fn flush_pending(
             );
             let ror = ror_owned.as_deref();
             aggregator.add_entry(doi, name, normalized, ror, provider_id, client_id);
+            batch_size += 1;
+            if let Some(m) = metrics {
+                if ror.is_some() {
+                    m.with_ror.add(1, &[]);
+                } else {
+                    m.without_ror.add(1, &[]);
+                }
+            }
         }
     }
+    if let Some(m) = metrics {
+        m.flush_batch_size.record(batch_size, &[]);
+    }
     pending.clear();
 }
 
@@ -697,7 +1551,13 @@ fn non_empty_str(value: &str) -> Option<&str> {
     }
 }
 
-fn normalize_text(text: &str) -> Option<String> {
+/// Deunicodes, lowercases, and strips to alphanumerics/whitespace. This is
+/// the full normalization for `--normalize-mode basic` (the default) and
+/// also the fuzzy-matching normalization used by `build_ror_clusters` and
+/// `suggest_ror_assignments`, independent of `--normalize-mode`; `tokens`
+/// and `tokens-sorted` run this as their first stage before stopword
+/// stripping in `normalize_text`.
+fn normalize_text_basic(text: &str) -> Option<String> {
     if text.is_empty() {
         return None;
     }
@@ -724,6 +1584,68 @@ fn is_latin_char_text(text: &str) -> bool {
     text.chars().any(|c| ('\u{0000}'..='\u{024F}').contains(&c))
 }
 
+/// Built-in stopwords dropped by `--normalize-mode tokens`/`tokens-sorted`:
+/// filler words plus organizational-noise terms (department/university and
+/// their abbreviations) so e.g. under `tokens-sorted` "Dept. of Physics, MIT"
+/// and "MIT, Department of Physics" both normalize down to "mit physics". `--stopwords` adds to,
+/// rather than replaces, this list.
+const BUILTIN_STOPWORDS: &[&str] = &[
+    "of", "the", "and", "for", "de", "la", "le", "der", "dept", "department", "univ", "university",
+];
+
+/// Resolved `--normalize-mode`/`--stopwords` settings, built once in `run`
+/// and threaded through to every `normalize_text` call.
+struct NormalizeConfig {
+    mode: NormalizeMode,
+    stopwords: FxHashSet<String>,
+}
+
+impl NormalizeConfig {
+    fn from_cli(cli: &Cli) -> Result<Self> {
+        let mut stopwords: FxHashSet<String> =
+            BUILTIN_STOPWORDS.iter().map(|s| s.to_string()).collect();
+        if let Some(path) = &cli.stopwords {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read stopwords file {}", path.display()))?;
+            for line in contents.lines() {
+                let word = line.trim().to_lowercase();
+                if !word.is_empty() {
+                    stopwords.insert(word);
+                }
+            }
+        }
+        Ok(Self {
+            mode: cli.normalize_mode,
+            stopwords,
+        })
+    }
+}
+
+/// Normalizes `text` for grouping into a `NormalizedCounts` bucket according
+/// to `config.mode`: `basic` is `normalize_text_basic` alone; `tokens` and
+/// `tokens-sorted` additionally split on whitespace, drop `config.stopwords`,
+/// and (for `tokens-sorted`) sort the remaining tokens, so word order and
+/// organizational filler no longer fragment the distribution.
+fn normalize_text(text: &str, config: &NormalizeConfig) -> Option<String> {
+    let cleaned = normalize_text_basic(text)?;
+    match config.mode {
+        NormalizeMode::Basic => Some(cleaned),
+        NormalizeMode::Tokens | NormalizeMode::TokensSorted => {
+            let mut tokens: Vec<&str> = cleaned
+                .split_whitespace()
+                .filter(|token| !config.stopwords.contains(*token))
+                .collect();
+            if tokens.is_empty() {
+                return None;
+            }
+            if config.mode == NormalizeMode::TokensSorted {
+                tokens.sort_unstable();
+            }
+            Some(tokens.join(" "))
+        }
+    }
+}
+
 lazy_static! {
     static ref ROR_REGEX: Regex =
         Regex::new(r"(?i)(?:https?://)?(?:www\.)?ror\.org/([0-9a-z]{9})").unwrap();
@@ -750,3 +1672,191 @@ fn normalize_ror_identifier(identifier: Option<&str>, scheme: Option<&str>) -> O
     }
     None
 }
+
+/// A normalized-affiliation cluster drawn from the `with_ror` records, used as
+/// the candidate set for fuzzy-matching unassigned affiliations against.
+struct RorCluster {
+    dominant_ror: String,
+    dominant_ror_count: u64,
+    total_count: u64,
+    representative_affiliation: String,
+}
+
+/// Groups `with_ror` records by their `normalize_text_basic` form, picking the
+/// highest-count ROR within each group as that cluster's dominant assignment.
+fn build_ror_clusters(with_ror: &[AffiliationWithRorRecord]) -> FxHashMap<String, RorCluster> {
+    let mut ror_counts_by_normalized: FxHashMap<String, FxHashMap<String, u64>> =
+        FxHashMap::default();
+    let mut totals: FxHashMap<String, u64> = FxHashMap::default();
+    let mut representatives: FxHashMap<String, (String, u64)> = FxHashMap::default();
+
+    for record in with_ror {
+        let Some(normalized) = normalize_text_basic(&record.affiliation) else {
+            continue;
+        };
+        *totals.entry(normalized.clone()).or_insert(0) += record.occurrences;
+        let ror_counts = ror_counts_by_normalized.entry(normalized.clone()).or_default();
+        for (ror, count) in &record.ror_assignments {
+            *ror_counts.entry(ror.clone()).or_insert(0) += count;
+        }
+        let best_so_far = representatives
+            .entry(normalized)
+            .or_insert_with(|| (record.affiliation.clone(), record.occurrences));
+        if record.occurrences > best_so_far.1 {
+            *best_so_far = (record.affiliation.clone(), record.occurrences);
+        }
+    }
+
+    ror_counts_by_normalized
+        .into_iter()
+        .filter_map(|(normalized, ror_counts)| {
+            let (dominant_ror, dominant_ror_count) = ror_counts
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+            let total_count = totals.get(&normalized).copied().unwrap_or(dominant_ror_count);
+            let representative_affiliation = representatives
+                .remove(&normalized)
+                .map(|(affiliation, _)| affiliation)?;
+            Some((
+                normalized,
+                RorCluster {
+                    dominant_ror,
+                    dominant_ror_count,
+                    total_count,
+                    representative_affiliation,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn first_token(normalized: &str) -> &str {
+    normalized.split_whitespace().next().unwrap_or("")
+}
+
+/// Plain-DP Levenshtein distance, used only to report the matched edit
+/// distance; candidate discovery itself goes through the `fst` automaton.
+fn levenshtein_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<u32> = (0..=b.len() as u32).collect();
+    let mut curr = vec![0u32; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i as u32 + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Suggests ROR IDs for `without_ror` affiliations by approximate string
+/// matching against the normalized `with_ror` clusters: a Levenshtein
+/// automaton over an `fst::Set` finds every assigned cluster within the edit
+/// budget, and a suggestion is only kept when the candidates agree on a
+/// single ROR (directly, or by one ROR dominating the matches by count) and
+/// share a first token with the query, to avoid collapsing distinct
+/// institutions that merely look similar.
+fn suggest_ror_assignments(
+    with_ror: &[AffiliationWithRorRecord],
+    without_ror: &[AffiliationWithoutRorRecord],
+    max_edits: u32,
+    min_confidence: f64,
+) -> Result<Vec<SuggestedRorRecord>> {
+    let clusters = build_ror_clusters(with_ror);
+    if clusters.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut normalized_keys: Vec<&str> = clusters.keys().map(String::as_str).collect();
+    normalized_keys.sort_unstable();
+    normalized_keys.dedup();
+    let set = Set::from_iter(normalized_keys.iter().copied())
+        .context("Build fst::Set of normalized assigned affiliations")?;
+
+    let mut suggestions = Vec::new();
+    for record in without_ror {
+        let Some(normalized) = normalize_text_basic(&record.affiliation) else {
+            continue;
+        };
+        let budget = if normalized.chars().count() <= 8 { 1 } else { 2 }.min(max_edits);
+        let automaton = match Levenshtein::new(&normalized, budget) {
+            Ok(automaton) => automaton,
+            Err(err) => {
+                warn!(
+                    "Skipping fuzzy ROR match for '{}': {}",
+                    record.affiliation, err
+                );
+                continue;
+            }
+        };
+
+        let mut matched_normalized = Vec::new();
+        let mut stream = set.search(&automaton).into_stream();
+        while let Some(key) = stream.next() {
+            let candidate = String::from_utf8_lossy(key).into_owned();
+            if first_token(&candidate) == first_token(&normalized) {
+                matched_normalized.push(candidate);
+            }
+        }
+
+        let mut counts_by_ror: FxHashMap<String, u64> = FxHashMap::default();
+        for candidate in &matched_normalized {
+            if let Some(cluster) = clusters.get(candidate) {
+                *counts_by_ror.entry(cluster.dominant_ror.clone()).or_insert(0) +=
+                    cluster.total_count;
+            }
+        }
+        if counts_by_ror.is_empty() {
+            continue;
+        }
+
+        let mut ranked: Vec<(String, u64)> = counts_by_ror.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1));
+        if ranked.len() > 1 && ranked[0].1 == ranked[1].1 {
+            continue;
+        }
+        let (winning_ror, winning_count) = ranked[0].clone();
+        let total_matched: u64 = ranked.iter().map(|(_, count)| count).sum();
+        let confidence = winning_count as f64 / total_matched as f64;
+        if confidence < min_confidence {
+            continue;
+        }
+
+        let Some((matched_affiliation, edit_distance)) = matched_normalized
+            .iter()
+            .filter(|candidate| {
+                clusters
+                    .get(*candidate)
+                    .is_some_and(|cluster| cluster.dominant_ror == winning_ror)
+            })
+            .map(|candidate| {
+                let distance = levenshtein_distance(&normalized, candidate);
+                let representative = clusters[candidate].representative_affiliation.clone();
+                (representative, distance)
+            })
+            .min_by_key(|(_, distance)| *distance)
+        else {
+            continue;
+        };
+
+        suggestions.push(SuggestedRorRecord {
+            affiliation: record.affiliation.clone(),
+            normalized,
+            suggested_ror: winning_ror,
+            matched_affiliation,
+            edit_distance,
+            confidence,
+        });
+    }
+
+    suggestions.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.affiliation.cmp(&b.affiliation))
+    });
+    Ok(suggestions)
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use tempfile::tempdir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One flattened-CSV row per `(field_name, subfield_path, value)` triple,
+    /// mirroring what fast-field-parser emits: several rows share a `doi`,
+    /// each contributing one attribute of one affiliation.
+    fn sample_csv() -> String {
+        let mut rows = vec!["doi,provider_id,client_id,field_name,subfield_path,value".to_string()];
+        for i in 0..20 {
+            let doi = format!("10.1/doi-{i}");
+            let provider = format!("prov{}", i % 3);
+            let client = format!("client{}", i % 4);
+            rows.push(format!(
+                "{doi},{provider},{client},creators,creators[0].affiliation[0].name,University {i}"
+            ));
+            rows.push(format!(
+                "{doi},{provider},{client},creators,creators[0].affiliation[0].affiliationIdentifier,https://ror.org/{i:08}"
+            ));
+            rows.push(format!(
+                "{doi},{provider},{client},creators,creators[0].affiliation[0].affiliationIdentifierScheme,ROR"
+            ));
+            rows.push(format!(
+                "{doi},{provider},{client},contributors,contributors[0].affiliation[0].name,University {i}"
+            ));
+        }
+        rows.join("\n") + "\n"
+    }
+
+    fn run_with_storage(input_csv: &Path, output_dir: &Path, doi_storage: &str) -> std::process::Output {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "-i",
+                input_csv.to_str().unwrap(),
+                "-o",
+                output_dir.to_str().unwrap(),
+                "--doi-storage",
+                doi_storage,
+            ])
+            .output()
+            .expect("failed to run affiliation-parsing")
+    }
+
+    #[test]
+    fn strings_and_bitmap_doi_storage_produce_identical_sorted_dois() {
+        let temp_dir = tempdir().unwrap();
+        let input_csv = temp_dir.path().join("input.csv");
+        fs::write(&input_csv, sample_csv()).unwrap();
+
+        let strings_dir = temp_dir.path().join("strings-out");
+        let bitmap_dir = temp_dir.path().join("bitmap-out");
+        fs::create_dir_all(&strings_dir).unwrap();
+        fs::create_dir_all(&bitmap_dir).unwrap();
+
+        let strings_output = run_with_storage(&input_csv, &strings_dir, "strings");
+        assert!(
+            strings_output.status.success(),
+            "strings run failed: {}",
+            String::from_utf8_lossy(&strings_output.stderr)
+        );
+        let bitmap_output = run_with_storage(&input_csv, &bitmap_dir, "bitmap");
+        assert!(
+            bitmap_output.status.success(),
+            "bitmap run failed: {}",
+            String::from_utf8_lossy(&bitmap_output.stderr)
+        );
+
+        for filename in [
+            "normalized_affiliation_doi_distribution.json",
+            "ror_identifier_doi_distribution.json",
+        ] {
+            let strings_json: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(strings_dir.join(filename)).unwrap()).unwrap();
+            let bitmap_json: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(bitmap_dir.join(filename)).unwrap()).unwrap();
+            assert_eq!(
+                strings_json, bitmap_json,
+                "{filename} differs between --doi-storage strings and --doi-storage bitmap"
+            );
+        }
+    }
+}
@@ -1,28 +1,112 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use arrow::array::{RecordBatch as ArrowRecordBatch, StringArray, StringDictionaryBuilder};
+use arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, Int32Type, Schema as ArrowSchema};
 use clap::{App, Arg};
+use crossbeam_channel::{bounded, unbounded, Sender};
 use csv::Writer;
 use flate2::read::GzDecoder;
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use log::{error, info, warn, LevelFilter};
+use parquet::arrow::ArrowWriter;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use simple_logger::SimpleLogger;
 
-#[cfg(target_os = "linux")]
-use std::fs::read_to_string;
-#[cfg(target_os = "macos")]
-use std::process::Command;
-#[cfg(target_os = "windows")]
-use std::process::Command as WinCommand;
+/// Named fault-injection points for testing the producer/consumer pipeline's
+/// resilience to partial failure: a source file erroring mid-read, a batch
+/// send failing, the writer dying before or after a batch, or `flush_all`
+/// itself failing. Live only behind the `failpoints` feature; even then a
+/// point only fires once armed via the `FAILPOINTS` env var at startup
+/// (`name=error,name2=panic`; any value other than `panic` arms `Error`).
+/// Outside the feature, `check` is an `#[inline(always)]` function that
+/// always returns `None`, so every call site compiles away to nothing and
+/// release builds pay zero overhead.
+#[cfg(feature = "failpoints")]
+mod failpoints {
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
 
-#[derive(Debug, Clone)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Action {
+        Error,
+        Panic,
+    }
+
+    fn armed() -> &'static HashMap<String, Action> {
+        static ARMED: OnceLock<HashMap<String, Action>> = OnceLock::new();
+        ARMED.get_or_init(|| {
+            std::env::var("FAILPOINTS")
+                .ok()
+                .map(|spec| {
+                    spec.split(',')
+                        .filter_map(|entry| {
+                            let (name, action) = entry.split_once('=')?;
+                            let action = if action == "panic" { Action::Panic } else { Action::Error };
+                            Some((name.to_string(), action))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn check(name: &str) -> Option<Action> {
+        armed().get(name).copied()
+    }
+}
+
+#[cfg(not(feature = "failpoints"))]
+mod failpoints {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Action {
+        Error,
+        Panic,
+    }
+
+    #[inline(always)]
+    pub fn check(_name: &str) -> Option<Action> {
+        None
+    }
+}
+
+/// Fires the named failpoint inside a `Result`-returning function: a no-op
+/// unless `FAILPOINTS` armed `name` (and the binary was built with
+/// `--features failpoints`), in which case it panics or returns an error as
+/// armed. For call sites that aren't `Result`-returning (the writer
+/// thread's per-batch loop), use `fail_point_triggered` instead.
+macro_rules! fail_point {
+    ($name:expr) => {
+        match failpoints::check($name) {
+            Some(failpoints::Action::Panic) => panic!("failpoint '{}' armed to panic", $name),
+            Some(failpoints::Action::Error) => return Err(anyhow!("failpoint '{}' armed to fail", $name)),
+            None => {}
+        }
+    };
+}
+
+/// Checks `name` and, if armed to `Error`, returns `true` so the caller can
+/// take the same path it would on a real failure; panics immediately if
+/// armed to `Panic`. For non-`Result` call sites where `fail_point!`'s
+/// `return Err` doesn't apply.
+fn fail_point_triggered(name: &str) -> bool {
+    match failpoints::check(name) {
+        Some(failpoints::Action::Panic) => panic!("failpoint '{}' armed to panic", name),
+        Some(failpoints::Action::Error) => true,
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 struct AffiliationData {
     doi: String,
     name: String,
@@ -35,52 +119,452 @@ struct AffiliationData {
     client_id: String,
 }
 
+/// One already-sorted source of values for the k-way merge in
+/// [`SpillingDistinctSet::count_unique`]: either a spilled segment file or
+/// the still-resident in-memory set, sorted once up front.
+enum DistinctSource {
+    File(std::io::Lines<BufReader<File>>),
+    Memory(std::vec::IntoIter<String>),
+}
+
+impl Iterator for DistinctSource {
+    type Item = std::io::Result<String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            DistinctSource::File(lines) => lines.next(),
+            DistinctSource::Memory(values) => values.next().map(Ok),
+        }
+    }
+}
+
+/// A distinct-value counter whose memory is bounded regardless of input
+/// size. Values accumulate in a `HashSet` until their estimated byte size
+/// crosses `max_memory_bytes`, at which point the set is sorted and spilled
+/// as a newline-delimited segment under `spill_dir` and cleared, modeled on
+/// the spill-to-disk strategy used for sorting window partitions. The exact
+/// distinct count is recovered with a k-way merge over the spilled segments
+/// plus the residual set: the smallest pending value across all sources is
+/// popped from a min-heap in turn, and the counter only advances when it
+/// differs from the previously popped value, so a duplicate that straddles
+/// two segments still collapses to one.
+struct SpillingDistinctSet {
+    category: String,
+    spill_dir: PathBuf,
+    max_memory_bytes: usize,
+    resident: HashSet<String>,
+    resident_bytes: usize,
+    segment_paths: Vec<PathBuf>,
+}
+
+/// Serializable snapshot of a [`SpillingDistinctSet`] for the checkpoint
+/// cache: the small resident set is captured verbatim, while already
+/// spilled segments are referenced by path rather than inlined, so
+/// checkpointing a set with gigabytes spilled doesn't itself require
+/// gigabytes of JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DistinctSetCheckpoint {
+    category: String,
+    resident: Vec<String>,
+    segment_paths: Vec<PathBuf>,
+}
+
+impl SpillingDistinctSet {
+    /// Rough per-entry overhead of a `HashSet<String>` slot (heap allocation
+    /// header plus hash table bookkeeping). Doesn't need to be exact, only
+    /// enough to keep peak memory in the ballpark of `max_memory_bytes`.
+    const ENTRY_OVERHEAD_BYTES: usize = 48;
+
+    fn new(category: &str, spill_dir: PathBuf, max_memory_bytes: usize) -> Self {
+        Self {
+            category: category.to_string(),
+            spill_dir,
+            max_memory_bytes,
+            resident: HashSet::new(),
+            resident_bytes: 0,
+            segment_paths: Vec::new(),
+        }
+    }
+
+    fn to_checkpoint(&self) -> DistinctSetCheckpoint {
+        DistinctSetCheckpoint {
+            category: self.category.clone(),
+            resident: self.resident.iter().cloned().collect(),
+            segment_paths: self.segment_paths.clone(),
+        }
+    }
+
+    /// Rebuilds a set from a checkpoint snapshot. Segment files referenced
+    /// by the snapshot are kept as-is (they're expected to still exist on
+    /// disk from the run that wrote the checkpoint); a segment that's gone
+    /// missing is dropped with a warning rather than failing the whole
+    /// restore, since the worst case is an undercount, not a crash.
+    fn from_checkpoint(snapshot: DistinctSetCheckpoint, spill_dir: PathBuf, max_memory_bytes: usize) -> Self {
+        let resident_bytes = snapshot
+            .resident
+            .iter()
+            .map(|v| v.len() + Self::ENTRY_OVERHEAD_BYTES)
+            .sum();
+        let segment_paths = snapshot
+            .segment_paths
+            .into_iter()
+            .filter(|path| {
+                let exists = path.exists();
+                if !exists {
+                    warn!("Checkpoint references missing spill segment {}; dropping it from the restored count.", path.display());
+                }
+                exists
+            })
+            .collect();
+        Self {
+            category: snapshot.category,
+            spill_dir,
+            max_memory_bytes,
+            resident: snapshot.resident.into_iter().collect(),
+            resident_bytes,
+            segment_paths,
+        }
+    }
+
+    fn insert(&mut self, value: &str) -> Result<()> {
+        if self.resident.insert(value.to_string()) {
+            self.resident_bytes += value.len() + Self::ENTRY_OVERHEAD_BYTES;
+            if self.max_memory_bytes > 0 && self.resident_bytes >= self.max_memory_bytes {
+                self.spill()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorts and flushes the resident set to a new segment file, then
+    /// clears it so accumulation can continue with flat memory.
+    fn spill(&mut self) -> Result<()> {
+        if self.resident.is_empty() {
+            return Ok(());
+        }
+        let mut values: Vec<&str> = self.resident.iter().map(String::as_str).collect();
+        values.sort_unstable();
+        let segment_path = self.spill_dir.join(format!("{}-{:05}.txt", self.category, self.segment_paths.len()));
+        let mut writer = BufWriter::new(File::create(&segment_path).with_context(|| format!("Failed to create spill segment {}", segment_path.display()))?);
+        for value in &values {
+            writeln!(writer, "{value}")?;
+        }
+        writer.flush()?;
+        info!(
+            "Spilled {} resident {} values (~{} bytes) to {}",
+            values.len(),
+            self.category,
+            self.resident_bytes,
+            segment_path.display()
+        );
+        self.segment_paths.push(segment_path);
+        self.resident.clear();
+        self.resident_bytes = 0;
+        Ok(())
+    }
+
+    /// Computes the exact distinct count via a k-way merge of the spilled
+    /// segments and the resident set. Non-destructive, so it's safe to call
+    /// from periodic progress logging as well as at the end of the run.
+    fn count_unique(&self) -> Result<usize> {
+        let mut sources: Vec<DistinctSource> = Vec::with_capacity(self.segment_paths.len() + 1);
+        for path in &self.segment_paths {
+            let file = File::open(path).with_context(|| format!("Failed to open spill segment {}", path.display()))?;
+            sources.push(DistinctSource::File(BufReader::new(file).lines()));
+        }
+        if !self.resident.is_empty() {
+            let mut values: Vec<String> = self.resident.iter().cloned().collect();
+            values.sort_unstable();
+            sources.push(DistinctSource::Memory(values.into_iter()));
+        }
+
+        let mut heap: BinaryHeap<Reverse<(String, usize)>> = BinaryHeap::with_capacity(sources.len());
+        for (index, source) in sources.iter_mut().enumerate() {
+            if let Some(value) = source.next() {
+                heap.push(Reverse((value?, index)));
+            }
+        }
+
+        let mut count = 0usize;
+        let mut previous: Option<String> = None;
+        while let Some(Reverse((value, index))) = heap.pop() {
+            if previous.as_deref() != Some(value.as_str()) {
+                count += 1;
+            }
+            if let Some(next_value) = sources[index].next() {
+                heap.push(Reverse((next_value?, index)));
+            }
+            previous = Some(value);
+        }
+        Ok(count)
+    }
+}
+
+/// Constant-memory alternative to [`SpillingDistinctSet`]: a HyperLogLog
+/// sketch with `P` = 14 (`M` = 16384 one-byte registers, ~16 KB total
+/// regardless of input size). Each inserted value is hashed with FNV-1a (a
+/// fixed, unseeded hash, unlike `std`'s randomly-seeded default hasher, so a
+/// value rehashes to the same register across a checkpoint restore in a
+/// fresh process); the top `P` bits of the hash select a register and the
+/// leading-zero run (+1) of the remaining bits is the candidate rank stored
+/// there. `estimate()` applies the standard Flajolet et al. formula with the
+/// small-range (linear counting) and large-range corrections.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    const P: u32 = 14;
+    const M: usize = 1 << Self::P;
+    const TWO_POW_32: f64 = 4_294_967_296.0;
+
+    fn new() -> Self {
+        Self { registers: vec![0u8; Self::M] }
+    }
+
+    fn alpha_m() -> f64 {
+        0.7213 / (1.0 + 1.079 / (Self::M as f64))
+    }
+
+    fn fnv1a_hash(value: &str) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+        let mut hash = OFFSET_BASIS;
+        for byte in value.as_bytes() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+        hash
+    }
+
+    fn insert(&mut self, value: &str) {
+        let hash = Self::fnv1a_hash(value);
+        let index = (hash >> (64 - Self::P)) as usize;
+        let remaining = hash << Self::P;
+        let rank = (remaining.leading_zeros() as u8) + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Raw cardinality estimate with the small/large-range corrections
+    /// applied, per the original HyperLogLog paper.
+    fn estimate(&self) -> f64 {
+        let m = Self::M as f64;
+        let sum_inverse_powers: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw_estimate = Self::alpha_m() * m * m / sum_inverse_powers;
+
+        if raw_estimate <= 2.5 * m {
+            let zero_registers = self.registers.iter().filter(|&&r| r == 0).count();
+            if zero_registers > 0 {
+                return m * (m / zero_registers as f64).ln();
+            }
+        } else if raw_estimate > Self::TWO_POW_32 / 30.0 {
+            return -Self::TWO_POW_32 * (1.0 - raw_estimate / Self::TWO_POW_32).ln();
+        }
+        raw_estimate
+    }
+
+    /// Standard error of the estimate, ~1.04/sqrt(m) of the true
+    /// cardinality, expressed as an absolute count.
+    fn standard_error(&self) -> f64 {
+        (1.04 / (Self::M as f64).sqrt()) * self.estimate()
+    }
+
+    fn to_checkpoint(&self) -> HyperLogLogCheckpoint {
+        HyperLogLogCheckpoint { registers: self.registers.clone() }
+    }
+
+    fn from_checkpoint(snapshot: HyperLogLogCheckpoint) -> Self {
+        let mut registers = snapshot.registers;
+        registers.resize(Self::M, 0);
+        Self { registers }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HyperLogLogCheckpoint {
+    registers: Vec<u8>,
+}
+
+/// The distinct-count result of a [`DistinctCounter`]: `estimated_error` is
+/// `Some` only for the HyperLogLog backend, since the exact `HashSet`-backed
+/// one has no approximation to report.
+struct DistinctReport {
+    count: usize,
+    estimated_error: Option<f64>,
+}
+
+/// Either an exact, disk-spilling distinct count (the default) or an
+/// opt-in constant-memory HyperLogLog estimate, chosen per run by
+/// `--estimate-counts`. Swapping the backend only changes how
+/// `insert`/count work; everything above this (`IncrementalStats`,
+/// checkpointing, reporting) is written against the one enum so the two
+/// modes stay interchangeable.
+enum DistinctCounter {
+    Exact(SpillingDistinctSet),
+    Estimated(HyperLogLog),
+}
+
+impl DistinctCounter {
+    fn new(category: &str, spill_dir: PathBuf, max_memory_bytes: usize, exact: bool) -> Self {
+        if exact {
+            DistinctCounter::Exact(SpillingDistinctSet::new(category, spill_dir, max_memory_bytes))
+        } else {
+            DistinctCounter::Estimated(HyperLogLog::new())
+        }
+    }
+
+    fn insert(&mut self, value: &str) -> Result<()> {
+        match self {
+            DistinctCounter::Exact(set) => set.insert(value),
+            DistinctCounter::Estimated(hll) => {
+                hll.insert(value);
+                Ok(())
+            }
+        }
+    }
+
+    fn report(&self) -> Result<DistinctReport> {
+        match self {
+            DistinctCounter::Exact(set) => Ok(DistinctReport { count: set.count_unique()?, estimated_error: None }),
+            DistinctCounter::Estimated(hll) => Ok(DistinctReport {
+                count: hll.estimate().round() as usize,
+                estimated_error: Some(hll.standard_error()),
+            }),
+        }
+    }
+
+    fn to_checkpoint(&self) -> DistinctCounterCheckpoint {
+        match self {
+            DistinctCounter::Exact(set) => DistinctCounterCheckpoint::Exact(set.to_checkpoint()),
+            DistinctCounter::Estimated(hll) => DistinctCounterCheckpoint::Estimated(hll.to_checkpoint()),
+        }
+    }
+
+    fn from_checkpoint(snapshot: DistinctCounterCheckpoint, spill_dir: PathBuf, max_memory_bytes: usize) -> Self {
+        match snapshot {
+            DistinctCounterCheckpoint::Exact(set) => DistinctCounter::Exact(SpillingDistinctSet::from_checkpoint(set, spill_dir, max_memory_bytes)),
+            DistinctCounterCheckpoint::Estimated(hll) => DistinctCounter::Estimated(HyperLogLog::from_checkpoint(hll)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum DistinctCounterCheckpoint {
+    Exact(DistinctSetCheckpoint),
+    Estimated(HyperLogLogCheckpoint),
+}
+
+fn format_distinct_report(report: &DistinctReport) -> String {
+    match report.estimated_error {
+        Some(error) => format!("~{} (estimated, stderr ~{:.0})", report.count, error),
+        None => report.count.to_string(),
+    }
+}
+
 struct IncrementalStats {
-    unique_records: HashMap<String, bool>,
-    unique_persons: HashMap<String, bool>,
-    unique_affiliations: HashMap<String, bool>,
+    unique_records: DistinctCounter,
+    unique_persons: DistinctCounter,
+    unique_affiliations: DistinctCounter,
     total_affiliation_records: usize,
     processed_files: usize,
     providers: HashMap<String, usize>,
     clients: HashMap<String, usize>,
+    batches_submitted: usize,
+    batches_failed: usize,
 }
 
 impl IncrementalStats {
-    fn new() -> Self {
+    fn new(spill_dir: PathBuf, max_memory_bytes: usize, exact_counts: bool) -> Self {
         Self {
-            unique_records: HashMap::new(),
-            unique_persons: HashMap::new(),
-            unique_affiliations: HashMap::new(),
+            unique_records: DistinctCounter::new("dois", spill_dir.clone(), max_memory_bytes, exact_counts),
+            unique_persons: DistinctCounter::new("persons", spill_dir.clone(), max_memory_bytes, exact_counts),
+            unique_affiliations: DistinctCounter::new("affiliations", spill_dir, max_memory_bytes, exact_counts),
             total_affiliation_records: 0,
             processed_files: 0,
             providers: HashMap::new(),
             clients: HashMap::new(),
+            batches_submitted: 0,
+            batches_failed: 0,
+        }
+    }
+
+    /// Folds in the outcome of one `RestApiSink` batch submission, so
+    /// success/failure counts show up alongside the rest of the periodic
+    /// and final reporting without the sink needing its own channel back
+    /// to the CLI.
+    fn record_batch_result(&mut self, success: bool) {
+        if success {
+            self.batches_submitted += 1;
+        } else {
+            self.batches_failed += 1;
         }
     }
 
-    fn update(&mut self, affiliations: &[AffiliationData]) {
+    fn update(&mut self, affiliations: &[AffiliationData]) -> Result<()> {
         self.total_affiliation_records += affiliations.len();
         self.processed_files += 1;
         for affiliation in affiliations {
-            self.unique_records.insert(affiliation.doi.clone(), true);
-            self.unique_persons.insert(affiliation.name.clone(), true);
-            self.unique_affiliations.insert(affiliation.affiliation_name.clone(), true);
-            
+            self.unique_records.insert(&affiliation.doi)?;
+            self.unique_persons.insert(&affiliation.name)?;
+            self.unique_affiliations.insert(&affiliation.affiliation_name)?;
+
             // Track provider and client counts
             *self.providers.entry(affiliation.provider_id.clone()).or_insert(0) += 1;
             *self.clients.entry(affiliation.client_id.clone()).or_insert(0) += 1;
         }
+        Ok(())
     }
 
     fn log_current_stats(&self) {
         info!("Current Statistics:");
         info!("  Files processed: {}", self.processed_files);
         info!("  Total affiliation records: {}", self.total_affiliation_records);
-        info!("  Unique DOIs/records: {}", self.unique_records.len());
-        info!("  Unique persons: {}", self.unique_persons.len());
-        info!("  Unique affiliations: {}", self.unique_affiliations.len());
+        match self.unique_records.report() {
+            Ok(report) => info!("  Unique DOIs/records: {}", format_distinct_report(&report)),
+            Err(e) => error!("Error computing unique DOI count: {}", e),
+        }
+        match self.unique_persons.report() {
+            Ok(report) => info!("  Unique persons: {}", format_distinct_report(&report)),
+            Err(e) => error!("Error computing unique person count: {}", e),
+        }
+        match self.unique_affiliations.report() {
+            Ok(report) => info!("  Unique affiliations: {}", format_distinct_report(&report)),
+            Err(e) => error!("Error computing unique affiliation count: {}", e),
+        }
         info!("  Unique providers: {}", self.providers.len());
         info!("  Unique clients: {}", self.clients.len());
+        if self.batches_submitted > 0 || self.batches_failed > 0 {
+            info!("  Batches submitted: {} ({} failed)", self.batches_submitted, self.batches_failed);
+        }
+    }
+
+    fn to_checkpoint(&self) -> checkpoint::StatsCheckpoint {
+        checkpoint::StatsCheckpoint {
+            total_affiliation_records: self.total_affiliation_records,
+            processed_files: self.processed_files,
+            providers: self.providers.clone(),
+            clients: self.clients.clone(),
+            unique_records: self.unique_records.to_checkpoint(),
+            unique_persons: self.unique_persons.to_checkpoint(),
+            unique_affiliations: self.unique_affiliations.to_checkpoint(),
+            batches_submitted: self.batches_submitted,
+            batches_failed: self.batches_failed,
+        }
+    }
+
+    fn from_checkpoint(snapshot: checkpoint::StatsCheckpoint, spill_dir: PathBuf, max_memory_bytes: usize) -> Self {
+        Self {
+            unique_records: DistinctCounter::from_checkpoint(snapshot.unique_records, spill_dir.clone(), max_memory_bytes),
+            unique_persons: DistinctCounter::from_checkpoint(snapshot.unique_persons, spill_dir.clone(), max_memory_bytes),
+            unique_affiliations: DistinctCounter::from_checkpoint(snapshot.unique_affiliations, spill_dir, max_memory_bytes),
+            total_affiliation_records: snapshot.total_affiliation_records,
+            processed_files: snapshot.processed_files,
+            providers: snapshot.providers,
+            clients: snapshot.clients,
+            batches_submitted: snapshot.batches_submitted,
+            batches_failed: snapshot.batches_failed,
+        }
     }
 }
 
@@ -92,15 +576,161 @@ fn find_jsonl_gz_files<P: AsRef<Path>>(directory: P) -> Result<Vec<PathBuf>> {
     Ok(paths)
 }
 
-fn process_jsonl_file<P: AsRef<Path>>(
-    filepath: P, 
+/// Where `.jsonl.gz` shards are read from: local disk via `glob`, or a
+/// remote object store (`s3://`, `gs://`, `az://`) selected from the
+/// `--input` URI scheme. This mirrors the input-store abstraction used for
+/// remote DataCite dumps elsewhere in this workspace.
+mod store {
+    use super::*;
+    use object_store::path::Path as ObjectPath;
+    use object_store::ObjectStore as ArrowObjectStore;
+
+    #[derive(Debug, Clone)]
+    pub enum InputKey {
+        Local(PathBuf),
+        Remote { display: String, path: ObjectPath },
+    }
+
+    impl InputKey {
+        pub fn display(&self) -> String {
+            match self {
+                InputKey::Local(path) => path.display().to_string(),
+                InputKey::Remote { display, .. } => display.clone(),
+            }
+        }
+
+        /// The local filesystem path backing this key, if any. Used by the
+        /// checkpoint subsystem to stat mtime/size; remote keys have no
+        /// local metadata to compare against, so checkpointing always
+        /// reprocesses them.
+        pub fn local_path(&self) -> Option<&Path> {
+            match self {
+                InputKey::Local(path) => Some(path.as_path()),
+                InputKey::Remote { .. } => None,
+            }
+        }
+    }
+
+    pub trait Store: Send + Sync {
+        fn list(&self) -> Result<Vec<InputKey>, String>;
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>>;
+    }
+
+    struct LocalStore {
+        root: PathBuf,
+    }
+
+    impl Store for LocalStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            super::find_jsonl_gz_files(&self.root)
+                .map(|paths| paths.into_iter().map(InputKey::Local).collect())
+                .map_err(|e| e.to_string())
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            match key {
+                InputKey::Local(path) => {
+                    let file = File::open(path)?;
+                    Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+                }
+                InputKey::Remote { display, .. } => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("LocalStore cannot open remote key {display}"),
+                )),
+            }
+        }
+    }
+
+    /// Remote store backed by the `object_store` crate, which speaks S3, GCS,
+    /// and Azure Blob behind one `ObjectStore` trait. Credentials are
+    /// resolved via each backend's usual chain (environment, profile, or
+    /// instance metadata). Listing enumerates the whole prefix in one
+    /// paginated stream rather than a serial directory walk.
+    struct RemoteStore {
+        runtime: tokio::runtime::Runtime,
+        inner: Box<dyn ArrowObjectStore>,
+        prefix: ObjectPath,
+        display_root: String,
+    }
+
+    impl RemoteStore {
+        fn new(uri: &str) -> Result<Self, String> {
+            let url = url::Url::parse(uri).map_err(|e| format!("invalid input URI '{uri}': {e}"))?;
+            let (inner, prefix) = object_store::parse_url(&url)
+                .map_err(|e| format!("unsupported object store URI '{uri}': {e}"))?;
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| format!("failed to start async runtime for remote input: {e}"))?;
+            Ok(Self {
+                runtime,
+                inner,
+                prefix,
+                display_root: uri.trim_end_matches('/').to_string(),
+            })
+        }
+    }
+
+    impl Store for RemoteStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            let prefix = self.prefix.clone();
+            self.runtime.block_on(async {
+                use futures::TryStreamExt;
+                let entries: Vec<_> = self
+                    .inner
+                    .list(Some(&prefix))
+                    .try_collect()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(entries
+                    .into_iter()
+                    .map(|meta| meta.location)
+                    .filter(|path| path.as_ref().ends_with(".jsonl.gz"))
+                    .map(|path| InputKey::Remote {
+                        display: format!("{}/{}", self.display_root, path),
+                        path,
+                    })
+                    .collect())
+            })
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            let path = match key {
+                InputKey::Remote { path, .. } => path.clone(),
+                InputKey::Local(local_path) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("RemoteStore cannot open local path {}", local_path.display()),
+                    ))
+                }
+            };
+            let bytes = self
+                .runtime
+                .block_on(async { self.inner.get(&path).await?.bytes().await })
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+            Ok(Box::new(BufReader::new(GzDecoder::new(std::io::Cursor::new(bytes.to_vec())))))
+        }
+    }
+
+    /// Picks a `Store` implementation from `--input`'s scheme: a bare path
+    /// (or `file://` URI) goes to `LocalStore`; anything else (`s3://`,
+    /// `gs://`, `az://`) goes through `object_store`.
+    pub fn for_input(input: &str) -> Result<Box<dyn Store>, String> {
+        if let Some(path) = input.strip_prefix("file://") {
+            return Ok(Box::new(LocalStore { root: PathBuf::from(path) }));
+        }
+        if input.contains("://") {
+            return Ok(Box::new(RemoteStore::new(input)?));
+        }
+        Ok(Box::new(LocalStore { root: PathBuf::from(input) }))
+    }
+}
+
+fn process_jsonl_file(
+    reader: Box<dyn BufRead>,
+    display_name: &str,
     filter_provider: Option<&str>,
-    filter_client: Option<&str>
+    filter_client: Option<&str>,
 ) -> Result<Vec<AffiliationData>> {
-    let filepath = filepath.as_ref();
-    let file = File::open(filepath).with_context(|| format!("Failed to open file: {}", filepath.display()))?;
-    let decoder = GzDecoder::new(file);
-    let reader = BufReader::new(decoder);
+    fail_point!("process_jsonl_file");
     let mut affiliation_data = Vec::new();
     for (line_num, line) in reader.lines().enumerate() {
         match line {
@@ -112,40 +742,40 @@ fn process_jsonl_file<P: AsRef<Path>>(
                         let provider_id = match extract_provider_id(&record) {
                             Some(id) => id,
                             None => {
-                                warn!("No provider ID found in record at {}:{}", filepath.display(), line_num + 1);
+                                warn!("No provider ID found in record at {}:{}", display_name, line_num + 1);
                                 continue;
                             }
                         };
-                        
+
                         let client_id = match extract_client_id(&record) {
                             Some(id) => id,
                             None => {
-                                warn!("No client ID found in record at {}:{}", filepath.display(), line_num + 1);
+                                warn!("No client ID found in record at {}:{}", display_name, line_num + 1);
                                 continue;
                             }
                         };
-                        
+
                         // Apply provider/client filters if specified
                         if let Some(filter_prov) = filter_provider {
                             if filter_prov != provider_id {
                                 continue;
                             }
                         }
-                        
+
                         if let Some(filter_cli) = filter_client {
                             if filter_cli != client_id {
                                 continue;
                             }
                         }
-                        
+
                         let doi = match extract_doi(&record) {
                             Some(id) => id,
                             None => {
-                                warn!("No DOI found in record at {}:{}", filepath.display(), line_num + 1);
+                                warn!("No DOI found in record at {}:{}", display_name, line_num + 1);
                                 continue;
                             }
                         };
-                        
+
                         if let Some(creators) = record.pointer("/attributes/creators") {
                             if let Some(creators_array) = creators.as_array() {
                                 for creator in creators_array {
@@ -162,12 +792,12 @@ fn process_jsonl_file<P: AsRef<Path>>(
                         }
                     }
                     Err(e) => {
-                        warn!("Error parsing JSON from {}:{}: {}", filepath.display(), line_num + 1, e);
+                        warn!("Error parsing JSON from {}:{}: {}", display_name, line_num + 1, e);
                     }
                 }
             }
             Err(e) => {
-                error!("Error reading from {}: {}", filepath.display(), e);
+                error!("Error reading from {}: {}", display_name, e);
                 break;
             }
         }
@@ -248,123 +878,411 @@ fn format_elapsed(elapsed: Duration) -> String {
     }
 }
 
-#[cfg(target_os = "linux")]
-fn get_memory_usage() -> Option<(f64, f64, f64)> {
-    let pid = std::process::id();
-    let status_file = format!("/proc/{}/status", pid);
-    if let Ok(content) = read_to_string(status_file) {
-        let mut vm_rss = None;
-        let mut vm_size = None;
-        for line in content.lines() {
-            if line.starts_with("VmRSS:") {
-                vm_rss = line.split_whitespace().nth(1).and_then(|s| s.parse::<f64>().ok());
-            } else if line.starts_with("VmSize:") {
-                vm_size = line.split_whitespace().nth(1).and_then(|s| s.parse::<f64>().ok());
+/// Portable process/system resource sampling via `sysinfo`, replacing the
+/// old per-OS `ps`/`wmic`/`/proc` scraping (brittle to parse, RSS/VSZ only,
+/// and shelled out to an external process on every sample). `ResourceMonitor`
+/// keeps a `System` handle around and re-reads only this process plus
+/// overall CPU/memory on each `sample()`, so a long ingest run can poll it
+/// on every `--stats-interval` tick without repeatedly spawning subprocesses.
+mod resource_monitor {
+    use log::{info, warn};
+    use sysinfo::{Pid, System};
+
+    const MB: f64 = 1024.0 * 1024.0;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct ResourceSample {
+        pub rss_mb: f64,
+        pub virtual_mb: f64,
+        pub system_memory_percent: f64,
+        pub process_cpu_percent: f32,
+        pub thread_count: usize,
+        pub system_load_percent: f32,
+    }
+
+    pub struct ResourceMonitor {
+        system: System,
+        pid: Pid,
+    }
+
+    impl ResourceMonitor {
+        pub fn new() -> Self {
+            Self {
+                system: System::new_all(),
+                pid: Pid::from_u32(std::process::id()),
             }
         }
-        if let Ok(meminfo) = read_to_string("/proc/meminfo") {
-            for line in meminfo.lines() {
-                if line.starts_with("MemTotal:") {
-                    if let Some(mem_total_kb) = line.split_whitespace().nth(1).and_then(|s| s.parse::<f64>().ok()) {
-                        if let (Some(rss), Some(size)) = (vm_rss, vm_size) {
-                            return Some((rss / 1024.0, size / 1024.0, rss / mem_total_kb * 100.0));
-                        }
-                    }
-                }
+
+        /// Thread count isn't portably exposed per-process by `sysinfo`, so
+        /// this reports the size of the Rayon pool doing the actual
+        /// parsing work, which is the figure that matters for judging
+        /// parallelism here.
+        pub fn sample(&mut self) -> Option<ResourceSample> {
+            self.system.refresh_memory();
+            self.system.refresh_cpu_usage();
+            self.system.refresh_process(self.pid);
+            let process = self.system.process(self.pid)?;
+
+            let total_mb = self.system.total_memory() as f64 / MB;
+            let rss_mb = process.memory() as f64 / MB;
+            let virtual_mb = process.virtual_memory() as f64 / MB;
+            let system_memory_percent = if total_mb > 0.0 { rss_mb / total_mb * 100.0 } else { 0.0 };
+
+            let cpus = self.system.cpus();
+            let system_load_percent = if !cpus.is_empty() {
+                cpus.iter().map(|cpu| cpu.cpu_usage()).sum::<f32>() / cpus.len() as f32
+            } else {
+                0.0
+            };
+
+            Some(ResourceSample {
+                rss_mb,
+                virtual_mb,
+                system_memory_percent,
+                process_cpu_percent: process.cpu_usage(),
+                thread_count: rayon::current_num_threads(),
+                system_load_percent,
+            })
+        }
+    }
+
+    /// Logs a combined memory/CPU/throughput line for `note` (e.g.
+    /// "startup", "periodic check", "completion"). `records_per_sec` is
+    /// `None` at points where no elapsed-time baseline exists to derive a
+    /// rate from (startup, completion).
+    pub fn log(monitor: &mut ResourceMonitor, note: &str, records_per_sec: Option<f64>) {
+        match monitor.sample() {
+            Some(s) => {
+                let throughput = records_per_sec
+                    .map(|rate| format!(", {:.0} records/sec", rate))
+                    .unwrap_or_default();
+                info!(
+                    "Resources ({}): {:.1} MB RSS, {:.1} MB virtual ({:.1}% of system memory), {:.1}% process CPU, {} threads, {:.1}% avg system load{}",
+                    note,
+                    s.rss_mb,
+                    s.virtual_mb,
+                    s.system_memory_percent,
+                    s.process_cpu_percent,
+                    s.thread_count,
+                    s.system_load_percent,
+                    throughput
+                );
             }
+            None => warn!("Failed to sample process resource usage ({})", note),
         }
     }
-    None
 }
 
-#[cfg(target_os = "macos")]
-fn get_memory_usage() -> Option<(f64, f64, f64)> {
-    let pid = std::process::id();
-    let ps_output = Command::new("ps").args(&["-o", "rss=", "-p", &pid.to_string()]).output().ok()?;
-    let rss_kb = String::from_utf8_lossy(&ps_output.stdout).trim().parse::<f64>().ok()?;
-    let vsz_output = Command::new("ps").args(&["-o", "vsz=", "-p", &pid.to_string()]).output().ok()?;
-    let vsz_kb = String::from_utf8_lossy(&vsz_output.stdout).trim().parse::<f64>().ok()?;
-    let hw_mem_output = Command::new("sysctl").args(&["-n", "hw.memsize"]).output().ok()?;
-    let total_bytes = String::from_utf8_lossy(&hw_mem_output.stdout).trim().parse::<f64>().ok()?;
-    let total_kb = total_bytes / 1024.0;
-    let percent = (rss_kb / total_kb) * 100.0;
-    Some((rss_kb / 1024.0, vsz_kb / 1024.0, percent))
-}
-
-#[cfg(target_os = "windows")]
-fn get_memory_usage() -> Option<(f64, f64, f64)> {
-    let pid = std::process::id();
-    let wmic_output = WinCommand::new("wmic").args(&["process", "where", &format!("ProcessID={}", pid), "get", "WorkingSetSize,VirtualSize", "/format:csv"]).output().ok()?;
-    let output_str = String::from_utf8_lossy(&wmic_output.stdout);
-    let lines: Vec<&str> = output_str.lines().collect();
-    if lines.len() < 2 { return None; }
-    let data_parts: Vec<&str> = lines[1].split(',').collect();
-    if data_parts.len() < 3 { return None; }
-    let working_set_bytes = data_parts[1].parse::<f64>().ok()?;
-    let virtual_bytes = data_parts[2].parse::<f64>().ok()?;
-    let mem_output = WinCommand::new("wmic").args(&["computersystem", "get", "TotalPhysicalMemory", "/format:value"]).output().ok()?;
-    let mem_str = String::from_utf8_lossy(&mem_output.stdout);
-    let total_bytes_str = mem_str.trim().strip_prefix("TotalPhysicalMemory=")?.trim();
-    let total_bytes = total_bytes_str.parse::<f64>().ok()?;
-    let percent = (working_set_bytes / total_bytes) * 100.0;
-    Some((working_set_bytes / (1024.0 * 1024.0), virtual_bytes / (1024.0 * 1024.0), percent))
-}
-
-#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-fn get_memory_usage() -> Option<(f64, f64, f64)> {
-    None
-}
+/// Checkpoint/resume subsystem: serializes a manifest of every processed
+/// file (identified by mtime+size, not content hash, since these are
+/// multi-hundred-MB shards) alongside the accumulated `IncrementalStats` to
+/// a cache file under the output directory. On startup a file whose
+/// current mtime+size still matches its manifest entry is skipped
+/// entirely; anything new or changed is reprocessed, and the restored
+/// stats are merged in so running totals stay correct across the gap.
+mod checkpoint {
+    use super::*;
 
-fn log_memory_usage(note: &str) {
-    if let Some((rss_mb, vm_size_mb, percent)) = get_memory_usage() {
-        info!("Memory usage ({}): {:.1} MB physical, {:.1} MB virtual, {:.1}% of system memory", note, rss_mb, vm_size_mb, percent);
-    } else {
-        #[cfg(target_os = "linux")]
-        info!("Failed to get memory usage on Linux");
-        #[cfg(target_os = "macos")]
-        info!("Failed to get memory usage on macOS");
-        #[cfg(target_os = "windows")]
-        info!("Failed to get memory usage on Windows");
-        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-        info!("Memory usage tracking not available on this platform");
+    /// Bumped whenever the manifest's shape changes incompatibly. A
+    /// mismatched or unparsable cache file is treated as "no checkpoint"
+    /// rather than failing the run.
+    const CHECKPOINT_VERSION: u32 = 2;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ProcessedFileRecord {
+        pub path: String,
+        pub mtime_unix_secs: u64,
+        pub size_bytes: u64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct StatsCheckpoint {
+        pub total_affiliation_records: usize,
+        pub processed_files: usize,
+        pub providers: HashMap<String, usize>,
+        pub clients: HashMap<String, usize>,
+        pub unique_records: DistinctCounterCheckpoint,
+        pub unique_persons: DistinctCounterCheckpoint,
+        pub unique_affiliations: DistinctCounterCheckpoint,
+        pub batches_submitted: usize,
+        pub batches_failed: usize,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CheckpointManifest {
+        version: u32,
+        files: Vec<ProcessedFileRecord>,
+        stats: StatsCheckpoint,
+    }
+
+    /// Picks a cache-file path "under the output directory": next to the
+    /// output file for single-file output, inside the output directory
+    /// for `--organize`. Remote (`s3://`/`gs://`/`az://`) output has no
+    /// local directory to anchor a cache file to, so checkpointing is
+    /// unsupported there.
+    pub fn manifest_path(output: &str, organize: bool) -> Option<PathBuf> {
+        if output.contains("://") {
+            return None;
+        }
+        let output_path = Path::new(output);
+        if organize {
+            Some(output_path.join(".checkpoint.json"))
+        } else {
+            let file_name = output_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "output".to_string());
+            Some(output_path.with_file_name(format!("{file_name}.checkpoint.json")))
+        }
+    }
+
+    pub fn file_record(path: &Path) -> Result<ProcessedFileRecord> {
+        let metadata = fs::metadata(path).with_context(|| format!("Failed to stat {}", path.display()))?;
+        let mtime_unix_secs = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Ok(ProcessedFileRecord {
+            path: path.to_string_lossy().into_owned(),
+            mtime_unix_secs,
+            size_bytes: metadata.len(),
+        })
+    }
+
+    /// Loads a manifest, validating the version header; any I/O, parse, or
+    /// version mismatch falls back to "no checkpoint" (a full run) with a
+    /// warning rather than aborting.
+    pub fn load(path: &Path) -> Option<(Vec<ProcessedFileRecord>, StatsCheckpoint)> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("Failed to read checkpoint {}: {}. Starting a full run.", path.display(), e);
+                return None;
+            }
+        };
+        let manifest: CheckpointManifest = match serde_json::from_str(&contents) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                warn!("Checkpoint {} is corrupt or unreadable ({}). Starting a full run.", path.display(), e);
+                return None;
+            }
+        };
+        if manifest.version != CHECKPOINT_VERSION {
+            warn!(
+                "Checkpoint {} has version {} (expected {}). Starting a full run.",
+                path.display(),
+                manifest.version,
+                CHECKPOINT_VERSION
+            );
+            return None;
+        }
+        info!("Loaded checkpoint {}: {} files already processed.", path.display(), manifest.files.len());
+        Some((manifest.files, manifest.stats))
+    }
+
+    pub fn save(path: &Path, files: &[ProcessedFileRecord], stats: &StatsCheckpoint) -> Result<()> {
+        let manifest = CheckpointManifest {
+            version: CHECKPOINT_VERSION,
+            files: files.to_vec(),
+            stats: stats.clone(),
+        };
+        let contents = serde_json::to_string(&manifest)?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, contents).with_context(|| format!("Failed to write checkpoint {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path).with_context(|| format!("Failed to finalize checkpoint {}", path.display()))?;
+        Ok(())
     }
 }
 
-struct CsvWriterManager {
-    base_output_dir: PathBuf,
-    organize_by_provider: bool,
-    default_writer: Option<Writer<File>>,
-    // Using LRU cache pattern for file handles
-    current_writers: HashMap<(String, String), Writer<File>>,
-    // Track which files we've created already to ensure headers are written once
-    created_files: HashSet<PathBuf>,
-    max_open_files: usize,
-    headers: Vec<String>,
+/// Rough serialized size of one `AffiliationData`: its string fields plus a
+/// fixed per-record overhead for struct/heap bookkeeping, mirroring the
+/// `ENTRY_OVERHEAD_BYTES` estimate `SpillingDistinctSet` uses for the same
+/// purpose. Doesn't need to be exact, only close enough to translate a
+/// target byte budget into a target record count.
+const RECORD_OVERHEAD_BYTES: usize = 64;
+
+fn estimate_affiliation_bytes(affiliation: &AffiliationData) -> usize {
+    RECORD_OVERHEAD_BYTES
+        + affiliation.doi.len()
+        + affiliation.name.len()
+        + affiliation.category.len()
+        + affiliation.role.len()
+        + affiliation.affiliation_name.len()
+        + affiliation.affiliation_id.len()
+        + affiliation.affiliation_scheme.len()
+        + affiliation.provider_id.len()
+        + affiliation.client_id.len()
 }
 
-impl CsvWriterManager {
-    fn new<P: AsRef<Path>>(output_path: P, organize_by_provider: bool, max_open_files: usize) -> Result<Self> {
-        let path = output_path.as_ref();
-        let headers = vec![
-            "doi".to_string(),
-            "name".to_string(),
-            "category".to_string(),
-            "role".to_string(),
-            "affiliation_name".to_string(),
-            "affiliation_id".to_string(),
-            "affiliation_scheme".to_string(),
-            "provider_id".to_string(),
-            "client_id".to_string(),
-        ];
-        
-        if organize_by_provider {
-            fs::create_dir_all(path)?;
-            info!("Created output directory: {}", path.display());
-            info!("Using a maximum of {} open files at once", max_open_files);
-            
-            Ok(Self {
-                base_output_dir: path.to_path_buf(),
-                organize_by_provider,
+/// Picks how many `AffiliationData` records to accumulate per batch before
+/// handing it to the CSV writer thread. A fixed `batch_size` either
+/// over-serializes through the single writer channel (many cores, small
+/// records) or balloons memory (few cores, huge records), so once
+/// `--target-batch-bytes` is set this instead samples the average record
+/// size from the first few processed files and derives a batch size that
+/// keeps roughly one in-flight batch's worth of bytes per Rayon worker.
+/// Falls back to the static `--batch-size` until enough samples are in, or
+/// permanently when `--target-batch-bytes` is 0 (the default).
+struct AdaptiveBatchSizer {
+    static_batch_size: usize,
+    target_batch_bytes: usize,
+    num_threads: usize,
+    sample_state: Mutex<BatchSampleState>,
+    resolved_batch_size: AtomicUsize,
+}
+
+#[derive(Default)]
+struct BatchSampleState {
+    files_sampled: usize,
+    records_sampled: usize,
+    bytes_sampled: usize,
+}
+
+impl AdaptiveBatchSizer {
+    /// Number of files to sample before committing to a target batch size.
+    /// Small enough that the static fallback only governs the very first
+    /// handful of files on any run.
+    const SAMPLE_WINDOW_FILES: usize = 10;
+    const MIN_BATCH_SIZE: usize = 100;
+
+    fn new(static_batch_size: usize, target_batch_bytes: usize, num_threads: usize) -> Self {
+        Self {
+            static_batch_size,
+            target_batch_bytes,
+            num_threads: num_threads.max(1),
+            sample_state: Mutex::new(BatchSampleState::default()),
+            resolved_batch_size: AtomicUsize::new(0),
+        }
+    }
+
+    /// Folds one processed file's records into the running sample. A no-op
+    /// once adaptive sizing is disabled or already resolved.
+    fn observe(&self, affiliations: &[AffiliationData]) {
+        if self.target_batch_bytes == 0 || self.resolved_batch_size.load(Ordering::Relaxed) != 0 {
+            return;
+        }
+        let mut state = self.sample_state.lock().unwrap();
+        if self.resolved_batch_size.load(Ordering::Relaxed) != 0 {
+            return;
+        }
+        state.files_sampled += 1;
+        state.records_sampled += affiliations.len();
+        state.bytes_sampled += affiliations.iter().map(estimate_affiliation_bytes).sum::<usize>();
+
+        if state.files_sampled >= Self::SAMPLE_WINDOW_FILES && state.records_sampled > 0 {
+            let avg_bytes_per_record = (state.bytes_sampled / state.records_sampled).max(1);
+            let per_worker_budget_bytes = (self.target_batch_bytes / self.num_threads).max(avg_bytes_per_record);
+            let batch_size = (per_worker_budget_bytes / avg_bytes_per_record).max(Self::MIN_BATCH_SIZE);
+            info!(
+                "Adaptive batch sizing: sampled {} records across {} files (~{} bytes/record avg), {} workers; targeting {} records/batch.",
+                state.records_sampled, state.files_sampled, avg_bytes_per_record, self.num_threads, batch_size
+            );
+            self.resolved_batch_size.store(batch_size, Ordering::Relaxed);
+        }
+    }
+
+    /// The batch size to use right now: the resolved adaptive target once
+    /// sampling has converged, otherwise the static `--batch-size` fallback.
+    fn batch_size(&self) -> usize {
+        match self.resolved_batch_size.load(Ordering::Relaxed) {
+            0 => self.static_batch_size,
+            resolved => resolved,
+        }
+    }
+}
+
+/// Soft backpressure layered on top of the bounded producer→writer channel.
+/// The channel's own capacity is a hard ceiling; this tracks the live queue
+/// depth separately so producers can pause once it crosses a HIGH_WATER
+/// fraction of capacity and resume only once it's drained back down to
+/// LOW_WATER, the way a cache evictor debounces around its high/low marks
+/// rather than oscillating right at the limit.
+struct BackpressureGate {
+    high_water: usize,
+    low_water: usize,
+    depth: AtomicUsize,
+}
+
+impl BackpressureGate {
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    fn new(capacity: usize, high_water_ratio: f64, low_water_ratio: f64) -> Self {
+        let capacity = capacity.max(1);
+        let high_water = (((capacity as f64) * high_water_ratio).round() as usize).clamp(1, capacity);
+        let low_water = (((capacity as f64) * low_water_ratio).round() as usize).clamp(0, high_water);
+        Self { high_water, low_water, depth: AtomicUsize::new(0) }
+    }
+
+    fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    fn watermarks(&self) -> (usize, usize) {
+        (self.high_water, self.low_water)
+    }
+
+    fn mark_enqueued(&self) {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn mark_dequeued(&self) {
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Parks the calling producer thread while the queue is at or above
+    /// `high_water`, polling until it drains to `low_water` or below.
+    fn throttle(&self) {
+        if self.depth() < self.high_water {
+            return;
+        }
+        warn!("Writer queue depth reached high-water mark ({}); pausing producers until it drains to {}.", self.high_water, self.low_water);
+        loop {
+            std::thread::sleep(Self::POLL_INTERVAL);
+            if self.depth() <= self.low_water {
+                break;
+            }
+        }
+    }
+}
+
+fn csv_headers() -> Vec<String> {
+    vec![
+        "doi".to_string(),
+        "name".to_string(),
+        "category".to_string(),
+        "role".to_string(),
+        "affiliation_name".to_string(),
+        "affiliation_id".to_string(),
+        "affiliation_scheme".to_string(),
+        "provider_id".to_string(),
+        "client_id".to_string(),
+    ]
+}
+
+struct CsvWriterManager {
+    base_output_dir: PathBuf,
+    organize_by_provider: bool,
+    default_writer: Option<Writer<File>>,
+    // Using LRU cache pattern for file handles
+    current_writers: HashMap<(String, String), Writer<File>>,
+    // Track which files we've created already to ensure headers are written once
+    created_files: HashSet<PathBuf>,
+    max_open_files: usize,
+    headers: Vec<String>,
+}
+
+impl CsvWriterManager {
+    fn new<P: AsRef<Path>>(output_path: P, organize_by_provider: bool, max_open_files: usize) -> Result<Self> {
+        let path = output_path.as_ref();
+        let headers = csv_headers();
+
+        if organize_by_provider {
+            fs::create_dir_all(path)?;
+            info!("Created output directory: {}", path.display());
+            info!("Using a maximum of {} open files at once", max_open_files);
+            
+            Ok(Self {
+                base_output_dir: path.to_path_buf(),
+                organize_by_provider,
                 default_writer: None,
                 current_writers: HashMap::new(),
                 created_files: HashSet::new(),
@@ -489,109 +1407,769 @@ impl CsvWriterManager {
             info!("Flushing {} open CSV files", self.current_writers.len());
             info!("Total unique files created/opened: {}", self.created_files.len());
         }
-        
+
         Ok(())
     }
 }
 
-fn main() -> Result<()> {
-    let start_time = Instant::now();
-    let matches = App::new("Affiliation Metadata Extractor")
-        .version("1.2")
-        .about("Extracts affiliation metadata from compressed JSONL files")
-        .arg(Arg::with_name("input").short('i').long("input").value_name("INPUT").help("Directory containing JSONL.gz files").required(true))
-        .arg(Arg::with_name("output").short('o').long("output").value_name("OUTPUT").help("Output CSV file or directory").default_value("affiliation_metadata.csv"))
-        .arg(Arg::with_name("log-level").short('l').long("log-level").value_name("LEVEL").help("Logging level (DEBUG, INFO, WARN, ERROR)").default_value("INFO"))
-        .arg(Arg::with_name("threads").short('t').long("threads").value_name("THREADS").help("Number of threads to use (0 for auto)").default_value("0"))
-        .arg(Arg::with_name("batch-size").short('b').long("batch-size").value_name("SIZE").help("Number of records to process in a batch before writing to CSV").default_value("10000"))
-        .arg(Arg::with_name("stats-interval").short('s').long("stats-interval").value_name("INTERVAL").help("Interval in seconds to log statistics").default_value("60"))
-        .arg(Arg::with_name("organize").short('g').long("organize").help("Organize output by provider/client").takes_value(false))
-        .arg(Arg::with_name("provider").long("provider").value_name("PROVIDER_ID").help("Filter by provider ID"))
-        .arg(Arg::with_name("client").long("client").value_name("CLIENT_ID").help("Filter by client ID"))
-        .arg(Arg::with_name("max-open-files").long("max-open-files").value_name("MAX_FILES").help("Maximum number of open files when using --organize (default: 100)").default_value("100"))
-        .get_matches();
-    
-    let log_level = match matches.value_of("log-level").unwrap() {
-        "DEBUG" => LevelFilter::Debug,
-        "INFO" => LevelFilter::Info,
-        "WARN" => LevelFilter::Warn,
-        "ERROR" => LevelFilter::Error,
-        _ => LevelFilter::Info,
-    };
-    
-    SimpleLogger::new().with_level(log_level).init()?;
-    let input_dir = matches.value_of("input").unwrap();
-    let output_path = matches.value_of("output").unwrap();
-    let batch_size = matches.value_of("batch-size").unwrap().parse::<usize>().unwrap_or(10000);
-    let stats_interval = matches.value_of("stats-interval").unwrap().parse::<u64>().unwrap_or(60);
-    let organize_by_provider = matches.is_present("organize");
-    let filter_provider = matches.value_of("provider");
-    let filter_client = matches.value_of("client");
-    let max_open_files = matches.value_of("max-open-files").unwrap().parse::<usize>().unwrap_or(100);
-    
-    info!("Using batch size of {} records", batch_size);
-    info!("Statistics will be logged every {} seconds", stats_interval);
-    
-    if let Some(provider) = filter_provider {
-        info!("Filtering by provider ID: {}", provider);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Parquet,
+}
+
+/// Arrow schema for `AffiliationData` rows: `provider_id`, `client_id`,
+/// `affiliation_name`, and `affiliation_id` (the ROR/identifier column) are
+/// dictionary-encoded since they repeat heavily across an affiliation
+/// table, while the remaining columns stay plain `Utf8`.
+fn affiliation_parquet_schema() -> Arc<ArrowSchema> {
+    let dictionary_type = ArrowDataType::Dictionary(Box::new(ArrowDataType::Int32), Box::new(ArrowDataType::Utf8));
+    Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("doi", ArrowDataType::Utf8, false),
+        ArrowField::new("name", ArrowDataType::Utf8, false),
+        ArrowField::new("category", ArrowDataType::Utf8, false),
+        ArrowField::new("role", ArrowDataType::Utf8, false),
+        ArrowField::new("affiliation_name", dictionary_type.clone(), false),
+        ArrowField::new("affiliation_id", dictionary_type.clone(), false),
+        ArrowField::new("affiliation_scheme", ArrowDataType::Utf8, false),
+        ArrowField::new("provider_id", dictionary_type.clone(), false),
+        ArrowField::new("client_id", dictionary_type, false),
+    ]))
+}
+
+/// Builds one Arrow record batch column-by-column from a slice of
+/// `AffiliationData`, matching `affiliation_parquet_schema`'s column order.
+fn affiliation_record_batch(schema: &Arc<ArrowSchema>, records: &[&AffiliationData]) -> Result<ArrowRecordBatch> {
+    let doi: StringArray = records.iter().map(|a| a.doi.as_str()).collect();
+    let name: StringArray = records.iter().map(|a| a.name.as_str()).collect();
+    let category: StringArray = records.iter().map(|a| a.category.as_str()).collect();
+    let role: StringArray = records.iter().map(|a| a.role.as_str()).collect();
+    let affiliation_scheme: StringArray = records.iter().map(|a| a.affiliation_scheme.as_str()).collect();
+
+    let mut affiliation_name = StringDictionaryBuilder::<Int32Type>::new();
+    let mut affiliation_id = StringDictionaryBuilder::<Int32Type>::new();
+    let mut provider_id = StringDictionaryBuilder::<Int32Type>::new();
+    let mut client_id = StringDictionaryBuilder::<Int32Type>::new();
+    for affiliation in records {
+        affiliation_name.append_value(&affiliation.affiliation_name);
+        affiliation_id.append_value(&affiliation.affiliation_id);
+        provider_id.append_value(&affiliation.provider_id);
+        client_id.append_value(&affiliation.client_id);
     }
-    
-    if let Some(client) = filter_client {
-        info!("Filtering by client ID: {}", client);
+
+    Ok(ArrowRecordBatch::try_new(
+        Arc::clone(schema),
+        vec![
+            Arc::new(doi), Arc::new(name), Arc::new(category), Arc::new(role),
+            Arc::new(affiliation_name.finish()), Arc::new(affiliation_id.finish()),
+            Arc::new(affiliation_scheme), Arc::new(provider_id.finish()), Arc::new(client_id.finish()),
+        ],
+    )?)
+}
+
+/// Parquet counterpart to `CsvWriterManager`: same `organize_by_provider`
+/// sharding and open-file eviction, but writing row-group Parquet via
+/// `arrow`/`parquet` instead of `csv::Writer`.
+struct ParquetWriterManager {
+    base_output_dir: PathBuf,
+    organize_by_provider: bool,
+    schema: Arc<ArrowSchema>,
+    default_writer: Option<ArrowWriter<File>>,
+    current_writers: HashMap<(String, String), ArrowWriter<File>>,
+    max_open_files: usize,
+}
+
+impl ParquetWriterManager {
+    fn new<P: AsRef<Path>>(output_path: P, organize_by_provider: bool, max_open_files: usize) -> Result<Self> {
+        let path = output_path.as_ref();
+        let schema = affiliation_parquet_schema();
+
+        if organize_by_provider {
+            fs::create_dir_all(path)?;
+            info!("Created output directory: {}. Writing one Parquet file per provider/client.", path.display());
+            info!("Using a maximum of {} open files at once", max_open_files);
+
+            Ok(Self {
+                base_output_dir: path.to_path_buf(),
+                organize_by_provider,
+                schema,
+                default_writer: None,
+                current_writers: HashMap::new(),
+                max_open_files,
+            })
+        } else {
+            let file = File::create(path)?;
+            let writer = ArrowWriter::try_new(file, Arc::clone(&schema), None)?;
+
+            Ok(Self {
+                base_output_dir: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+                organize_by_provider,
+                schema,
+                default_writer: Some(writer),
+                current_writers: HashMap::new(),
+                max_open_files,
+            })
+        }
     }
-    
-    if organize_by_provider {
-        info!("Output will be organized by provider/client in directory: {}", output_path);
-    } else {
-        info!("Output will be written to single file: {}", output_path);
+
+    fn get_writer(&mut self, provider_id: &str, client_id: &str) -> Result<&mut ArrowWriter<File>> {
+        if !self.organize_by_provider {
+            return Ok(self.default_writer.as_mut().unwrap());
+        }
+
+        let key = (provider_id.to_string(), client_id.to_string());
+        if !self.current_writers.contains_key(&key) {
+            if self.current_writers.len() >= self.max_open_files {
+                let keys_to_remove: Vec<(String, String)> = self.current_writers.keys()
+                    .take(self.max_open_files / 2)
+                    .cloned()
+                    .collect();
+
+                info!("Reached {} open files limit, closing {} parquet writers", self.max_open_files, keys_to_remove.len());
+
+                for k in keys_to_remove {
+                    if let Some(mut writer) = self.current_writers.remove(&k) {
+                        if let Err(e) = writer.flush() {
+                            error!("Error flushing parquet writer while evicting: {}", e);
+                        }
+                    }
+                }
+            }
+
+            let provider_dir = self.base_output_dir.join(provider_id);
+            fs::create_dir_all(&provider_dir)?;
+
+            let client_file = provider_dir.join(format!("{}.parquet", client_id));
+            let file = File::create(&client_file)?;
+            let writer = ArrowWriter::try_new(file, Arc::clone(&self.schema), None)?;
+            info!("Created new parquet file: {}", client_file.display());
+
+            self.current_writers.insert(key.clone(), writer);
+        }
+
+        Ok(self.current_writers.get_mut(&key).unwrap())
     }
-    
-    if let Some(threads_str) = matches.value_of("threads") {
-        if let Ok(threads) = threads_str.parse::<usize>() {
-            if threads > 0 {
-                rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
-                info!("Using {} threads", threads);
+
+    fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+        let mut grouped_records: HashMap<(String, String), Vec<&AffiliationData>> = HashMap::new();
+
+        for affiliation in batch {
+            let key = (affiliation.provider_id.clone(), affiliation.client_id.clone());
+            grouped_records.entry(key).or_insert_with(Vec::new).push(affiliation);
+        }
+
+        for ((provider_id, client_id), records) in grouped_records {
+            let schema = Arc::clone(&self.schema);
+            let record_batch = affiliation_record_batch(&schema, &records)?;
+            let writer = self.get_writer(&provider_id, &client_id)?;
+            writer.write(&record_batch)?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes every open writer so each file gets its Parquet footer; only
+    /// a closed `ArrowWriter` leaves a readable file behind.
+    fn flush_all(&mut self) -> Result<()> {
+        if let Some(writer) = self.default_writer.take() {
+            writer.close()?;
+        }
+
+        let open_writers = self.current_writers.len();
+        for (_, writer) in self.current_writers.drain() {
+            writer.close()?;
+        }
+
+        if self.organize_by_provider {
+            info!("Closed {} open parquet writers", open_writers);
+        }
+
+        Ok(())
+    }
+}
+
+/// Where extracted affiliation rows are written: local CSV or Parquet
+/// file(s) via `CsvWriterManager`/`ParquetWriterManager`, or CSV objects in
+/// a remote store (`s3://`, `gs://`, `az://`) selected from the `--output`
+/// URI scheme, optionally alongside a `RestApiSink`.
+mod sink {
+    use super::*;
+    use object_store::path::Path as ObjectPath;
+    use object_store::ObjectStore as ArrowObjectStore;
+
+    pub trait Sink: Send {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()>;
+        fn flush_all(&mut self) -> Result<()>;
+    }
+
+    impl Sink for super::CsvWriterManager {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+            super::CsvWriterManager::write_batch(self, batch)
+        }
+        fn flush_all(&mut self) -> Result<()> {
+            super::CsvWriterManager::flush_all(self)
+        }
+    }
+
+    impl Sink for super::ParquetWriterManager {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+            super::ParquetWriterManager::write_batch(self, batch)
+        }
+        fn flush_all(&mut self) -> Result<()> {
+            super::ParquetWriterManager::flush_all(self)
+        }
+    }
+
+    /// Buffers CSV rows per provider/client key (or a single key when
+    /// `--organize` is off) and uploads each as one complete object via
+    /// `object_store`'s `put` once processing finishes. Remote object stores
+    /// have no local append semantics, so nothing is uploaded incrementally.
+    struct RemoteCsvSink {
+        runtime: tokio::runtime::Runtime,
+        inner: Box<dyn ArrowObjectStore>,
+        prefix: ObjectPath,
+        display_root: String,
+        organize_by_provider: bool,
+        headers: Vec<String>,
+        buffers: HashMap<(String, String), Writer<Vec<u8>>>,
+    }
+
+    impl RemoteCsvSink {
+        fn new(uri: &str, organize_by_provider: bool, headers: Vec<String>) -> Result<Self> {
+            let url = url::Url::parse(uri).with_context(|| format!("Invalid output URI '{uri}'"))?;
+            let (inner, prefix) = object_store::parse_url(&url)
+                .with_context(|| format!("Unsupported object store URI '{uri}'"))?;
+            let runtime = tokio::runtime::Runtime::new()
+                .context("Failed to start async runtime for remote output")?;
+            Ok(Self {
+                runtime,
+                inner,
+                prefix,
+                display_root: uri.trim_end_matches('/').to_string(),
+                organize_by_provider,
+                headers,
+                buffers: HashMap::new(),
+            })
+        }
+
+        fn key_for(&self, provider_id: &str, client_id: &str) -> (String, String) {
+            if self.organize_by_provider {
+                (provider_id.to_string(), client_id.to_string())
+            } else {
+                (String::new(), String::new())
+            }
+        }
+
+        fn buffer_for(&mut self, provider_id: &str, client_id: &str) -> Result<&mut Writer<Vec<u8>>> {
+            let key = self.key_for(provider_id, client_id);
+            if !self.buffers.contains_key(&key) {
+                let mut writer = Writer::from_writer(Vec::new());
+                writer.write_record(&self.headers)?;
+                self.buffers.insert(key.clone(), writer);
+            }
+            Ok(self.buffers.get_mut(&key).unwrap())
+        }
+
+        fn object_path(&self, provider_id: &str, client_id: &str) -> ObjectPath {
+            let suffix = if self.organize_by_provider {
+                format!("{}/{}.csv", provider_id, client_id)
+            } else {
+                "affiliation_metadata.csv".to_string()
+            };
+            let prefix_str = self.prefix.as_ref();
+            if prefix_str.is_empty() {
+                ObjectPath::from(suffix)
+            } else {
+                ObjectPath::from(format!("{}/{}", prefix_str, suffix))
             }
         }
     }
-    
-    log_memory_usage("startup");
-    info!("Finding files in {}...", input_dir);
-    let files = find_jsonl_gz_files(input_dir)?;
+
+    impl Sink for RemoteCsvSink {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+            let mut grouped_records: HashMap<(String, String), Vec<&AffiliationData>> = HashMap::new();
+            for affiliation in batch {
+                let key = self.key_for(&affiliation.provider_id, &affiliation.client_id);
+                grouped_records.entry(key).or_insert_with(Vec::new).push(affiliation);
+            }
+            for ((provider_id, client_id), records) in grouped_records {
+                let writer = self.buffer_for(&provider_id, &client_id)?;
+                for affiliation in records {
+                    writer.write_record(&[
+                        &affiliation.doi,
+                        &affiliation.name,
+                        &affiliation.category,
+                        &affiliation.role,
+                        &affiliation.affiliation_name,
+                        &affiliation.affiliation_id,
+                        &affiliation.affiliation_scheme,
+                        &affiliation.provider_id,
+                        &affiliation.client_id,
+                    ])?;
+                }
+            }
+            Ok(())
+        }
+
+        fn flush_all(&mut self) -> Result<()> {
+            let buffers = std::mem::take(&mut self.buffers);
+            for ((provider_id, client_id), writer) in buffers {
+                let bytes = writer
+                    .into_inner()
+                    .map_err(|e| anyhow!("Failed to flush CSV buffer for {}/{}: {}", provider_id, client_id, e))?;
+                let len = bytes.len();
+                let path = self.object_path(&provider_id, &client_id);
+                let display = format!("{}/{}", self.display_root, path.as_ref());
+                self.runtime
+                    .block_on(async { self.inner.put(&path, bytes.into()).await })
+                    .with_context(|| format!("Failed to upload {}", display))?;
+                info!("Uploaded {} ({} bytes)", display, len);
+            }
+            Ok(())
+        }
+    }
+
+    /// Batch payload posted to `--rest-endpoint`: an editgroup-style
+    /// submission grouping the batch's records under a named editgroup,
+    /// with an auto-accept toggle mirroring the editgroup review workflow
+    /// of the curation services this feeds.
+    #[derive(Serialize)]
+    struct EditgroupBatch<'a> {
+        editgroup: &'a str,
+        auto_accept: bool,
+        records: &'a [AffiliationData],
+    }
+
+    /// Pushes extracted affiliation records straight into a curation/ingest
+    /// service instead of the usual two-step file-then-upload workflow: each
+    /// batch handed to `write_batch` is POSTed as one editgroup submission.
+    /// Reuses the same single-threaded `Sink::write_batch` contract as the
+    /// file writers (no dedicated channel or thread of its own), so it can
+    /// run alongside `CsvWriterManager`/`ParquetWriterManager` via
+    /// `MultiSink`. A failed submission is retried with exponential backoff
+    /// up to `max_retries` times before being recorded as a failure in
+    /// `IncrementalStats`; `--rest-dry-run` logs the would-be submission
+    /// instead of sending it.
+    struct RestApiSink {
+        runtime: tokio::runtime::Runtime,
+        client: reqwest::Client,
+        endpoint: String,
+        editgroup: String,
+        auto_accept: bool,
+        dry_run: bool,
+        max_retries: u32,
+        retry_base: Duration,
+        stats: Arc<Mutex<super::IncrementalStats>>,
+    }
+
+    impl RestApiSink {
+        fn new(
+            endpoint: String,
+            editgroup: String,
+            auto_accept: bool,
+            dry_run: bool,
+            max_retries: u32,
+            retry_base: Duration,
+            stats: Arc<Mutex<super::IncrementalStats>>,
+        ) -> Result<Self> {
+            let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime for REST API sink")?;
+            Ok(Self {
+                runtime,
+                client: reqwest::Client::new(),
+                endpoint,
+                editgroup,
+                auto_accept,
+                dry_run,
+                max_retries,
+                retry_base,
+                stats,
+            })
+        }
+
+        fn submit(&self, payload: &EditgroupBatch) -> Result<()> {
+            self.runtime.block_on(async {
+                let response = self
+                    .client
+                    .post(&self.endpoint)
+                    .json(payload)
+                    .send()
+                    .await
+                    .with_context(|| format!("Failed to reach REST API sink endpoint {}", self.endpoint))?;
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+                let body = response.text().await.unwrap_or_default();
+                Err(anyhow!("REST API sink endpoint {} returned {}: {}", self.endpoint, status, body))
+            })
+        }
+    }
+
+    impl Sink for RestApiSink {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+            if batch.is_empty() {
+                return Ok(());
+            }
+            let payload = EditgroupBatch { editgroup: &self.editgroup, auto_accept: self.auto_accept, records: batch };
+            if self.dry_run {
+                info!(
+                    "[dry-run] Would submit {} record(s) to {} under editgroup '{}' (auto_accept={})",
+                    batch.len(),
+                    self.endpoint,
+                    self.editgroup,
+                    self.auto_accept
+                );
+                return Ok(());
+            }
+
+            let mut attempt = 0u32;
+            loop {
+                match self.submit(&payload) {
+                    Ok(()) => {
+                        if let Ok(mut stats) = self.stats.lock() {
+                            stats.record_batch_result(true);
+                        }
+                        return Ok(());
+                    }
+                    Err(e) if attempt < self.max_retries => {
+                        let backoff = self.retry_base * 2u32.pow(attempt);
+                        warn!(
+                            "REST API sink batch submission failed ({}); retrying in {:?} (attempt {}/{})",
+                            e,
+                            backoff,
+                            attempt + 1,
+                            self.max_retries
+                        );
+                        std::thread::sleep(backoff);
+                        attempt += 1;
+                    }
+                    Err(e) => {
+                        if let Ok(mut stats) = self.stats.lock() {
+                            stats.record_batch_result(false);
+                        }
+                        return Err(e.context(format!("Giving up after {} attempt(s)", attempt + 1)));
+                    }
+                }
+            }
+        }
+
+        fn flush_all(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Fans one `write_batch`/`flush_all` call out to every configured sink,
+    /// so a run can write files and submit to a `RestApiSink` in the same
+    /// pass without a second channel or writer thread. A failure in one sink
+    /// is logged and doesn't stop the batch from reaching the others.
+    struct MultiSink {
+        sinks: Vec<Box<dyn Sink>>,
+    }
+
+    impl Sink for MultiSink {
+        fn write_batch(&mut self, batch: &[AffiliationData]) -> Result<()> {
+            for sink in self.sinks.iter_mut() {
+                if let Err(e) = sink.write_batch(batch) {
+                    error!("Error writing batch to a configured sink: {}", e);
+                }
+            }
+            Ok(())
+        }
+
+        fn flush_all(&mut self) -> Result<()> {
+            for sink in self.sinks.iter_mut() {
+                if let Err(e) = sink.flush_all() {
+                    error!("Error flushing a configured sink: {}", e);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// If `--rest-endpoint` was given, wraps `primary` and a new
+    /// `RestApiSink` in a `MultiSink` so both receive every batch; otherwise
+    /// returns `primary` unchanged.
+    #[allow(clippy::too_many_arguments)]
+    pub fn maybe_with_rest(
+        primary: Box<dyn Sink>,
+        endpoint: Option<&str>,
+        editgroup: &str,
+        auto_accept: bool,
+        dry_run: bool,
+        max_retries: u32,
+        retry_base: Duration,
+        stats: Arc<Mutex<super::IncrementalStats>>,
+    ) -> Result<Box<dyn Sink>> {
+        let Some(endpoint) = endpoint else { return Ok(primary) };
+        info!(
+            "REST API sink enabled: submitting batches to {} under editgroup '{}' (auto_accept={}, dry_run={})",
+            endpoint, editgroup, auto_accept, dry_run
+        );
+        let rest_sink = RestApiSink::new(endpoint.to_string(), editgroup.to_string(), auto_accept, dry_run, max_retries, retry_base, stats)?;
+        Ok(Box::new(MultiSink { sinks: vec![primary, Box::new(rest_sink)] }))
+    }
+
+    /// Picks a `Sink` from `output`'s scheme: a bare path (or `file://` URI)
+    /// writes local file(s) via `CsvWriterManager` or `ParquetWriterManager`
+    /// per `output_format`; anything else (`s3://`, `gs://`, `az://`) goes
+    /// through `object_store` and is always written as CSV.
+    pub fn for_output(
+        output: &str,
+        organize_by_provider: bool,
+        max_open_files: usize,
+        headers: Vec<String>,
+        output_format: super::OutputFormat,
+    ) -> Result<Box<dyn Sink>> {
+        let path = output.strip_prefix("file://").unwrap_or(output);
+        if output.contains("://") && path == output {
+            if output_format == super::OutputFormat::Parquet {
+                warn!("Parquet output isn't supported for remote object-store destinations yet; writing CSV instead.");
+            }
+            warn!("--max-open-files is ignored for remote output; rows are buffered in memory per provider/client and uploaded once processing finishes.");
+            return Ok(Box::new(RemoteCsvSink::new(output, organize_by_provider, headers)?));
+        }
+        match output_format {
+            super::OutputFormat::Csv => Ok(Box::new(super::CsvWriterManager::new(path, organize_by_provider, max_open_files)?)),
+            super::OutputFormat::Parquet => Ok(Box::new(super::ParquetWriterManager::new(path, organize_by_provider, max_open_files)?)),
+        }
+    }
+}
+
+/// A stage of `process_directory`'s run, reported on its progress channel
+/// alongside `ProgressData`'s counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProgressStage {
+    Discovering,
+    Processing,
+    Finalizing,
+}
+
+/// A snapshot of `process_directory`'s progress, sent on its progress
+/// channel. `files_to_check` is 0 until file discovery (and checkpoint
+/// filtering) finish and the true pending count is known.
+#[derive(Debug, Clone)]
+struct ProgressData {
+    stage: ProgressStage,
+    files_checked: usize,
+    files_to_check: usize,
+    records_processed: usize,
+}
+
+/// Everything `process_directory` needs besides the stop flag and progress
+/// channel: the parsed CLI options, so a caller embedding the extractor can
+/// construct one without going through `clap` at all.
+struct ProcessDirectoryOptions {
+    input: String,
+    output: String,
+    batch_size: usize,
+    target_batch_bytes: usize,
+    stats_interval: u64,
+    organize_by_provider: bool,
+    filter_provider: Option<String>,
+    filter_client: Option<String>,
+    max_open_files: usize,
+    max_distinct_memory_bytes: usize,
+    checkpoint_enabled: bool,
+    exact_counts: bool,
+    output_format: OutputFormat,
+    channel_capacity: usize,
+    high_water_ratio: f64,
+    low_water_ratio: f64,
+    rest_endpoint: Option<String>,
+    rest_editgroup: String,
+    rest_auto_accept: bool,
+    rest_dry_run: bool,
+    rest_max_retries: u32,
+    rest_retry_base: Duration,
+}
+
+/// The outcome of a `process_directory` run, including one cancelled
+/// partway through by the stop flag.
+struct ProcessSummary {
+    total_files: usize,
+    files_processed: usize,
+    total_affiliation_records: usize,
+    cancelled: bool,
+}
+
+/// Finds input files, extracts affiliation metadata from every one not
+/// already checkpointed, and writes output as it goes. Progress is reported
+/// on `progress_tx` rather than drawn directly, so both the CLI and any
+/// embedding caller can drive the same run; `stop` is polled between files
+/// in the Rayon loop so a caller can request a clean early return. On
+/// cancellation the already-collected batch is still sent to the writer
+/// thread and `flush_all` and stats finalization (including the checkpoint
+/// save) still run, exactly as on a normal completion, so no buffered
+/// output is lost.
+fn process_directory(
+    options: ProcessDirectoryOptions,
+    stop: Arc<AtomicBool>,
+    progress_tx: Sender<ProgressData>,
+) -> Result<ProcessSummary> {
+    let send_progress = |stage: ProgressStage, files_checked: usize, files_to_check: usize, records_processed: usize| {
+        let _ = progress_tx.send(ProgressData { stage, files_checked, files_to_check, records_processed });
+    };
+    let filter_provider = options.filter_provider.as_deref();
+    let filter_client = options.filter_client.as_deref();
+    let output_path = options.output.as_str();
+
+    send_progress(ProgressStage::Discovering, 0, 0, 0);
+    let mut resource_monitor = resource_monitor::ResourceMonitor::new();
+    resource_monitor::log(&mut resource_monitor, "startup", None);
+    info!("Finding files in {}...", options.input);
+    let input_store: Arc<dyn store::Store> = Arc::from(
+        store::for_input(&options.input).map_err(|e| anyhow!("{}", e))?,
+    );
+    let files = input_store.list().map_err(|e| anyhow!("{}", e))?;
     info!("Found {} files to process", files.len());
-    
+
     if files.is_empty() {
-        warn!("No files found in {}. Exiting.", input_dir);
-        return Ok(());
+        warn!("No files found in {}. Exiting.", options.input);
+        return Ok(ProcessSummary { total_files: 0, files_processed: 0, total_affiliation_records: 0, cancelled: false });
     }
-    
-    let progress_bar = ProgressBar::new(files.len() as u64);
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("#>-")
+
+    let checkpoint_manifest_path = if options.checkpoint_enabled {
+        match checkpoint::manifest_path(output_path, options.organize_by_provider) {
+            Some(path) => Some(path),
+            None => {
+                warn!("--checkpoint has no effect for remote output ({}): there is no local directory to cache a manifest under.", output_path);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let (prior_files, restored_stats) = match &checkpoint_manifest_path {
+        Some(path) => checkpoint::load(path).map_or((Vec::new(), None), |(files, stats)| (files, Some(stats))),
+        None => (Vec::new(), None),
+    };
+    let prior_files_by_path: HashMap<String, checkpoint::ProcessedFileRecord> =
+        prior_files.into_iter().map(|record| (record.path.clone(), record)).collect();
+
+    let mut carried_over_records = Vec::new();
+    let files: Vec<store::InputKey> = files
+        .into_iter()
+        .filter(|key| {
+            let Some(local_path) = key.local_path() else { return true; };
+            let Some(prior) = prior_files_by_path.get(&local_path.to_string_lossy().into_owned()) else { return true; };
+            match checkpoint::file_record(local_path) {
+                Ok(current) if current.mtime_unix_secs == prior.mtime_unix_secs && current.size_bytes == prior.size_bytes => {
+                    carried_over_records.push(prior.clone());
+                    false
+                }
+                _ => true,
+            }
+        })
+        .collect();
+
+    if checkpoint_manifest_path.is_some() && !carried_over_records.is_empty() {
+        info!("Checkpoint: skipping {} file(s) unchanged since the last run.", carried_over_records.len());
+    }
+    if files.is_empty() {
+        info!("All input files are unchanged since the last checkpointed run. Nothing to do.");
+        return Ok(ProcessSummary { total_files: 0, files_processed: 0, total_affiliation_records: 0, cancelled: false });
+    }
+    let total_files = files.len();
+    send_progress(ProgressStage::Processing, 0, total_files, 0);
+
+    // A checkpointed run needs its spilled segments to survive past this
+    // process exiting, so they can be referenced again on the next run;
+    // without checkpointing the spill directory is purely scratch space
+    // for this run and is removed on exit (including on early returns via
+    // `?`, since `TempDir`'s `Drop` runs regardless).
+    let (_distinct_spill_tempdir, distinct_spill_dir) = match &checkpoint_manifest_path {
+        Some(manifest_path) => {
+            let dir = manifest_path.with_extension("spill");
+            fs::create_dir_all(&dir).with_context(|| format!("Failed to create spill directory {}", dir.display()))?;
+            (None, dir)
+        }
+        None => {
+            let tempdir = tempfile::tempdir().context("Failed to create spill directory for distinct-value tracking")?;
+            let path = tempdir.path().to_path_buf();
+            (Some(tempdir), path)
+        }
+    };
+    if options.max_distinct_memory_bytes > 0 {
+        info!("Spilling unique-value trackers over {} MB to {}", options.max_distinct_memory_bytes / (1024 * 1024), distinct_spill_dir.display());
+    }
+
+    info!(
+        "Using a bounded writer channel with capacity {} (high-water {:.0}%, low-water {:.0}%)",
+        options.channel_capacity,
+        options.high_water_ratio * 100.0,
+        options.low_water_ratio * 100.0
     );
-    progress_bar.set_message("Processing files...");
-    
-    let mut csv_writer_manager = CsvWriterManager::new(output_path, organize_by_provider, max_open_files)?;
-    
-    let (tx, rx) = std::sync::mpsc::channel::<Option<Vec<AffiliationData>>>();
+    let (tx, rx) = bounded::<Option<Vec<AffiliationData>>>(options.channel_capacity);
+    let queue_gate = Arc::new(BackpressureGate::new(options.channel_capacity, options.high_water_ratio, options.low_water_ratio));
+    let stats = Arc::new(Mutex::new(match restored_stats {
+        Some(snapshot) => {
+            info!("Restored checkpointed stats: {} affiliation records across {} prior file(s).", snapshot.total_affiliation_records, snapshot.processed_files);
+            IncrementalStats::from_checkpoint(snapshot, distinct_spill_dir.clone(), options.max_distinct_memory_bytes)
+        }
+        None => IncrementalStats::new(distinct_spill_dir.clone(), options.max_distinct_memory_bytes, options.exact_counts),
+    }));
+    let csv_writer_manager = sink::for_output(output_path, options.organize_by_provider, options.max_open_files, csv_headers(), options.output_format)?;
+    let csv_writer_manager = sink::maybe_with_rest(
+        csv_writer_manager,
+        options.rest_endpoint.as_deref(),
+        &options.rest_editgroup,
+        options.rest_auto_accept,
+        options.rest_dry_run,
+        options.rest_max_retries,
+        options.rest_retry_base,
+        Arc::clone(&stats),
+    )?;
     let csv_writer_mutex = Arc::new(Mutex::new(csv_writer_manager));
-    let stats = Arc::new(Mutex::new(IncrementalStats::new()));
+    let completed_file_records = Arc::new(Mutex::new(carried_over_records));
     let stats_clone = Arc::clone(&stats);
     let stats_thread_running = Arc::new(Mutex::new(true));
     let stats_thread_running_clone = Arc::clone(&stats_thread_running);
-    
+    let checkpoint_stats_clone = Arc::clone(&stats);
+    let checkpoint_records_clone = Arc::clone(&completed_file_records);
+    let checkpoint_manifest_path_clone = checkpoint_manifest_path.clone();
+    let queue_gate_for_stats = Arc::clone(&queue_gate);
+    let stats_interval = options.stats_interval;
+    let channel_capacity = options.channel_capacity;
+
     let stats_thread = std::thread::spawn(move || {
         let mut last_log_time = Instant::now();
+        let mut monitor = resource_monitor::ResourceMonitor::new();
+        let mut previous_total_records = 0usize;
+        let mut previous_sample_time = Instant::now();
         loop {
             std::thread::sleep(Duration::from_secs(1));
             if last_log_time.elapsed().as_secs() >= stats_interval {
-                log_memory_usage("periodic check");
+                let current_total_records = stats_clone.lock().map(|s| s.total_affiliation_records).unwrap_or(previous_total_records);
+                let elapsed_secs = previous_sample_time.elapsed().as_secs_f64();
+                let records_per_sec = if elapsed_secs > 0.0 {
+                    Some((current_total_records.saturating_sub(previous_total_records)) as f64 / elapsed_secs)
+                } else {
+                    None
+                };
+                resource_monitor::log(&mut monitor, "periodic check", records_per_sec);
+                previous_total_records = current_total_records;
+                previous_sample_time = Instant::now();
                 if let Ok(stats) = stats_clone.lock() {
                     stats.log_current_stats();
                 }
+                let (high_water, low_water) = queue_gate_for_stats.watermarks();
+                info!(
+                    "Writer queue depth: {}/{} batches (high-water {}, low-water {})",
+                    queue_gate_for_stats.depth(),
+                    channel_capacity,
+                    high_water,
+                    low_water
+                );
+                if let Some(manifest_path) = &checkpoint_manifest_path_clone {
+                    let snapshot = checkpoint_stats_clone.lock().ok().map(|s| s.to_checkpoint());
+                    let records = checkpoint_records_clone.lock().ok().map(|r| r.clone());
+                    if let (Some(snapshot), Some(records)) = (snapshot, records) {
+                        if let Err(e) = checkpoint::save(manifest_path, &records, &snapshot) {
+                            error!("Error writing checkpoint {}: {}", manifest_path.display(), e);
+                        }
+                    }
+                }
                 last_log_time = Instant::now();
             }
             if let Ok(running) = stats_thread_running_clone.lock() {
@@ -601,118 +2179,378 @@ fn main() -> Result<()> {
             }
         }
     });
-    
+
+    let queue_gate_for_writer = Arc::clone(&queue_gate);
     let csv_writer_thread = std::thread::spawn(move || {
         let mut writer_manager = csv_writer_mutex.lock().unwrap();
         while let Ok(batch_option) = rx.recv() {
             match batch_option {
                 Some(batch) => {
-                    if let Err(e) = writer_manager.write_batch(&batch) {
+                    queue_gate_for_writer.mark_dequeued();
+                    if fail_point_triggered("write_batch_before") {
+                        error!("Error writing batch to CSV: failpoint 'write_batch_before' armed to fail");
+                    } else if let Err(e) = writer_manager.write_batch(&batch) {
                         error!("Error writing batch to CSV: {}", e);
                     }
+                    if fail_point_triggered("write_batch_after") {
+                        error!("Error writing batch to CSV: failpoint 'write_batch_after' armed to fail");
+                    }
                 }
                 None => {
                     break;
                 }
             }
         }
-        if let Err(e) = writer_manager.flush_all() {
+        if fail_point_triggered("flush_all") {
+            error!("Error flushing CSV writers: failpoint 'flush_all' armed to fail");
+        } else if let Err(e) = writer_manager.flush_all() {
             error!("Error flushing CSV writers: {}", e);
         }
     });
-    
-    let batch_collector = Arc::new(Mutex::new(Vec::with_capacity(batch_size)));
-    
-    files.par_iter().for_each(|filepath| {
-        match process_jsonl_file(filepath, filter_provider, filter_client) {
+
+    let batch_sizer = AdaptiveBatchSizer::new(options.batch_size, options.target_batch_bytes, rayon::current_num_threads());
+    let batch_collector = Arc::new(Mutex::new(Vec::with_capacity(options.batch_size)));
+    let files_done = Arc::new(AtomicUsize::new(0));
+    let records_done = Arc::new(AtomicUsize::new(0));
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    files.par_iter().for_each(|key| {
+        if stop.load(Ordering::Relaxed) {
+            cancelled.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        let display_name = key.display();
+        let outcome = input_store
+            .open(key)
+            .map_err(|e| anyhow!("{}", e))
+            .and_then(|reader| process_jsonl_file(reader, &display_name, filter_provider, filter_client));
+        match outcome {
             Ok(file_affiliations) => {
-                let file_name = filepath.file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| filepath.to_string_lossy().to_string());
-                
-                progress_bar.set_message(format!("Processed: {}", file_name));
-                
+                if checkpoint_manifest_path.is_some() {
+                    if let Some(local_path) = key.local_path() {
+                        match checkpoint::file_record(local_path) {
+                            Ok(record) => completed_file_records.lock().unwrap().push(record),
+                            Err(e) => warn!("Failed to stat {} for checkpointing: {}", display_name, e),
+                        }
+                    }
+                }
+
                 if !file_affiliations.is_empty() {
+                    batch_sizer.observe(&file_affiliations);
+                    records_done.fetch_add(file_affiliations.len(), Ordering::Relaxed);
+
                     let mut stats_guard = stats.lock().unwrap();
-                    stats_guard.update(&file_affiliations);
+                    if let Err(e) = stats_guard.update(&file_affiliations) {
+                        error!("Error updating distinct-value tracking for {}: {}", display_name, e);
+                    }
                     drop(stats_guard);
-                    
+
                     let mut batch_guard = batch_collector.lock().unwrap();
                     batch_guard.extend(file_affiliations);
-                    
-                    if batch_guard.len() >= batch_size {
-                        let batch_to_send = std::mem::replace(&mut *batch_guard, Vec::with_capacity(batch_size));
+
+                    let current_batch_size = batch_sizer.batch_size();
+                    if batch_guard.len() >= current_batch_size {
+                        let batch_to_send = std::mem::replace(&mut *batch_guard, Vec::with_capacity(current_batch_size));
                         drop(batch_guard);
-                        
-                        if let Err(e) = tx.send(Some(batch_to_send)) {
-                            error!("Error sending batch to CSV writer: {}", e);
+
+                        if fail_point_triggered("tx_send") {
+                            error!("Error sending batch to CSV writer: failpoint 'tx_send' armed to fail");
+                        } else {
+                            queue_gate.throttle();
+                            // Mark enqueued before the send actually lands so the
+                            // writer thread's mark_dequeued() (on receipt) can never
+                            // race ahead of this and underflow the depth counter.
+                            queue_gate.mark_enqueued();
+                            if let Err(e) = tx.send(Some(batch_to_send)) {
+                                error!("Error sending batch to CSV writer: {}", e);
+                                queue_gate.mark_dequeued();
+                            }
                         }
                     }
                 }
             }
             Err(e) => {
-                error!("Error processing {}: {}", filepath.display(), e);
+                error!("Error processing {}: {}", display_name, e);
             }
         }
-        progress_bar.inc(1);
+        let done = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+        send_progress(ProgressStage::Processing, done, total_files, records_done.load(Ordering::Relaxed));
     });
-    
+
     let remaining_batch = {
         let mut batch_guard = batch_collector.lock().unwrap();
         std::mem::replace(&mut *batch_guard, Vec::new())
     };
-    
+
     if !remaining_batch.is_empty() {
-        if let Err(e) = tx.send(Some(remaining_batch)) {
-            error!("Error sending final batch to CSV writer: {}", e);
+        if fail_point_triggered("tx_send") {
+            error!("Error sending final batch to CSV writer: failpoint 'tx_send' armed to fail");
+        } else {
+            queue_gate.throttle();
+            queue_gate.mark_enqueued();
+            if let Err(e) = tx.send(Some(remaining_batch)) {
+                error!("Error sending final batch to CSV writer: {}", e);
+                queue_gate.mark_dequeued();
+            }
         }
     }
-    
+
     if let Err(e) = tx.send(None) {
         error!("Error sending end signal to CSV writer: {}", e);
     }
-    
+
     if let Err(e) = csv_writer_thread.join() {
         error!("Error joining CSV writer thread: {:?}", e);
     }
-    
-    progress_bar.finish_with_message(format!("Completed in {}", format_elapsed(start_time.elapsed())));
-    
+
+    let files_processed = files_done.load(Ordering::Relaxed);
+    send_progress(ProgressStage::Finalizing, files_processed, total_files, records_done.load(Ordering::Relaxed));
+
     {
         let stats_guard = stats.lock().unwrap();
         info!("Final Statistics:");
         info!("  Files processed: {}", stats_guard.processed_files);
         info!("  Total affiliation records: {}", stats_guard.total_affiliation_records);
-        info!("  Unique DOIs/records: {}", stats_guard.unique_records.len());
-        info!("  Unique persons: {}", stats_guard.unique_persons.len());
-        info!("  Unique affiliations: {}", stats_guard.unique_affiliations.len());
+        info!("  Unique DOIs/records: {}", format_distinct_report(&stats_guard.unique_records.report()?));
+        info!("  Unique persons: {}", format_distinct_report(&stats_guard.unique_persons.report()?));
+        info!("  Unique affiliations: {}", format_distinct_report(&stats_guard.unique_affiliations.report()?));
         info!("  Unique providers: {}", stats_guard.providers.len());
         info!("  Unique clients: {}", stats_guard.clients.len());
-        
+
         info!("Provider statistics:");
         for (provider, count) in stats_guard.providers.iter() {
             info!("  Provider {}: {} records", provider, count);
         }
-        
+
         info!("Client statistics:");
         for (client, count) in stats_guard.clients.iter() {
             info!("  Client {}: {} records", client, count);
         }
+
+        if let Some(manifest_path) = &checkpoint_manifest_path {
+            let records = completed_file_records.lock().unwrap();
+            if let Err(e) = checkpoint::save(manifest_path, &records, &stats_guard.to_checkpoint()) {
+                error!("Error writing final checkpoint {}: {}", manifest_path.display(), e);
+            } else {
+                info!("Checkpoint saved to {} ({} file(s) recorded).", manifest_path.display(), records.len());
+            }
+        }
     }
-    
-    log_memory_usage("completion");
-    
+
+    resource_monitor::log(&mut resource_monitor, "completion", None);
+
     {
         let mut running = stats_thread_running.lock().unwrap();
         *running = false;
     }
-    
+
     if let Err(e) = stats_thread.join() {
         error!("Error joining stats thread: {:?}", e);
     }
-    
+
+    Ok(ProcessSummary {
+        total_files,
+        files_processed,
+        total_affiliation_records: stats.lock().unwrap().total_affiliation_records,
+        cancelled: cancelled.load(Ordering::Relaxed),
+    })
+}
+
+fn main() -> Result<()> {
+    let start_time = Instant::now();
+    let matches = App::new("Affiliation Metadata Extractor")
+        .version("1.3")
+        .about("Extracts affiliation metadata from compressed JSONL files")
+        .arg(Arg::with_name("input").short('i').long("input").value_name("INPUT").help("Directory containing JSONL.gz files, or an s3://, gs://, or az:// URI").required(true))
+        .arg(Arg::with_name("output").short('o').long("output").value_name("OUTPUT").help("Output CSV file or directory, or an s3://, gs://, or az:// URI").default_value("affiliation_metadata.csv"))
+        .arg(Arg::with_name("log-level").short('l').long("log-level").value_name("LEVEL").help("Logging level (DEBUG, INFO, WARN, ERROR)").default_value("INFO"))
+        .arg(Arg::with_name("threads").short('t').long("threads").value_name("THREADS").help("Number of threads to use (0 for auto)").default_value("0"))
+        .arg(Arg::with_name("batch-size").short('b').long("batch-size").value_name("SIZE").help("Number of records to process in a batch before writing to CSV").default_value("10000"))
+        .arg(Arg::with_name("target-batch-bytes").long("target-batch-bytes").value_name("BYTES").help("Size batches dynamically from the observed average record size instead of a fixed --batch-size, targeting roughly this many buffered bytes in total, split evenly across worker threads; 0 disables adaptive sizing (default)").default_value("0"))
+        .arg(Arg::with_name("stats-interval").short('s').long("stats-interval").value_name("INTERVAL").help("Interval in seconds to log statistics").default_value("60"))
+        .arg(Arg::with_name("organize").short('g').long("organize").help("Organize output by provider/client").takes_value(false))
+        .arg(Arg::with_name("provider").long("provider").value_name("PROVIDER_ID").help("Filter by provider ID"))
+        .arg(Arg::with_name("client").long("client").value_name("CLIENT_ID").help("Filter by client ID"))
+        .arg(Arg::with_name("max-open-files").long("max-open-files").value_name("MAX_FILES").help("Maximum number of open files when using --organize (default: 100)").default_value("100"))
+        .arg(Arg::with_name("max-distinct-memory-mb").long("max-distinct-memory-mb").value_name("MB").help("Approximate memory budget in MB for each unique-value tracker (DOIs, persons, affiliations) before it spills sorted segments to a temp directory; 0 keeps everything in memory (default)").default_value("0"))
+        .arg(Arg::with_name("checkpoint").long("checkpoint").help("Cache processed-file mtime/size and accumulated stats under the output directory, and skip unchanged files on the next run").takes_value(false))
+        .arg(Arg::with_name("estimate-counts").long("estimate-counts").help("Track unique DOIs/persons/affiliations with constant-memory HyperLogLog estimates instead of exact HashSets").takes_value(false))
+        .arg(Arg::with_name("output-format").long("output-format").value_name("FORMAT").help("Output format: csv or parquet (dictionary-encoded, row-group Parquet)").default_value("csv"))
+        .arg(Arg::with_name("channel-capacity").long("channel-capacity").value_name("BATCHES").help("Maximum number of batches buffered between producer threads and the CSV writer thread").default_value("64"))
+        .arg(Arg::with_name("high-water-ratio").long("high-water-ratio").value_name("RATIO").help("Fraction of --channel-capacity at which producer threads pause to let the writer catch up").default_value("0.8"))
+        .arg(Arg::with_name("low-water-ratio").long("low-water-ratio").value_name("RATIO").help("Fraction of --channel-capacity the queue must drain back to before paused producers resume").default_value("0.5"))
+        .arg(Arg::with_name("rest-endpoint").long("rest-endpoint").value_name("URL").help("Also POST each batch as an editgroup submission to this curation/ingest REST endpoint"))
+        .arg(Arg::with_name("rest-editgroup").long("rest-editgroup").value_name("NAME").help("Editgroup/submission name batches are grouped under when --rest-endpoint is set").default_value("affiliation-parser-import"))
+        .arg(Arg::with_name("rest-auto-accept").long("rest-auto-accept").help("Request that submitted editgroups be auto-accepted by the REST endpoint").takes_value(false))
+        .arg(Arg::with_name("rest-dry-run").long("rest-dry-run").help("Log what would be submitted to --rest-endpoint without actually sending it").takes_value(false))
+        .arg(Arg::with_name("rest-max-retries").long("rest-max-retries").value_name("COUNT").help("Number of times to retry a failed batch submission with exponential backoff before giving up on it").default_value("5"))
+        .arg(Arg::with_name("rest-retry-base-ms").long("rest-retry-base-ms").value_name("MS").help("Base delay for exponential backoff between retried batch submissions").default_value("500"))
+        .get_matches();
+
+    let log_level = match matches.value_of("log-level").unwrap() {
+        "DEBUG" => LevelFilter::Debug,
+        "INFO" => LevelFilter::Info,
+        "WARN" => LevelFilter::Warn,
+        "ERROR" => LevelFilter::Error,
+        _ => LevelFilter::Info,
+    };
+
+    SimpleLogger::new().with_level(log_level).init()?;
+    let input = matches.value_of("input").unwrap().to_string();
+    let output = matches.value_of("output").unwrap().to_string();
+    let batch_size = matches.value_of("batch-size").unwrap().parse::<usize>().unwrap_or(10000);
+    let target_batch_bytes = matches.value_of("target-batch-bytes").unwrap().parse::<usize>().unwrap_or(0);
+    let stats_interval = matches.value_of("stats-interval").unwrap().parse::<u64>().unwrap_or(60);
+    let organize_by_provider = matches.is_present("organize");
+    let filter_provider = matches.value_of("provider").map(String::from);
+    let filter_client = matches.value_of("client").map(String::from);
+    let max_open_files = matches.value_of("max-open-files").unwrap().parse::<usize>().unwrap_or(100);
+    let max_distinct_memory_bytes = matches.value_of("max-distinct-memory-mb").unwrap().parse::<usize>().unwrap_or(0).saturating_mul(1024 * 1024);
+    let checkpoint_enabled = matches.is_present("checkpoint");
+    let exact_counts = !matches.is_present("estimate-counts");
+    let output_format = match matches.value_of("output-format").unwrap().to_lowercase().as_str() {
+        "parquet" => OutputFormat::Parquet,
+        "csv" => OutputFormat::Csv,
+        other => {
+            warn!("Unrecognized --output-format '{}'; defaulting to csv", other);
+            OutputFormat::Csv
+        }
+    };
+    let channel_capacity = matches.value_of("channel-capacity").unwrap().parse::<usize>().unwrap_or(64);
+    let mut high_water_ratio = matches.value_of("high-water-ratio").unwrap().parse::<f64>().unwrap_or(0.8);
+    let mut low_water_ratio = matches.value_of("low-water-ratio").unwrap().parse::<f64>().unwrap_or(0.5);
+    if low_water_ratio >= high_water_ratio {
+        warn!(
+            "--low-water-ratio ({}) must be below --high-water-ratio ({}); falling back to the defaults 0.5/0.8.",
+            low_water_ratio, high_water_ratio
+        );
+        low_water_ratio = 0.5;
+        high_water_ratio = 0.8;
+    }
+    let rest_endpoint = matches.value_of("rest-endpoint").map(String::from);
+    let rest_editgroup = matches.value_of("rest-editgroup").unwrap().to_string();
+    let rest_auto_accept = matches.is_present("rest-auto-accept");
+    let rest_dry_run = matches.is_present("rest-dry-run");
+    let rest_max_retries = matches.value_of("rest-max-retries").unwrap().parse::<u32>().unwrap_or(5);
+    let rest_retry_base = Duration::from_millis(matches.value_of("rest-retry-base-ms").unwrap().parse::<u64>().unwrap_or(500));
+
+    if target_batch_bytes > 0 {
+        info!("Adaptive batch sizing enabled: targeting ~{} buffered bytes split across worker threads (falling back to {} records until sampled).", target_batch_bytes, batch_size);
+    } else {
+        info!("Using batch size of {} records", batch_size);
+    }
+    if exact_counts {
+        info!("Tracking unique DOIs/persons/affiliations with exact HashSets (pass --estimate-counts for constant-memory HyperLogLog estimates)");
+    } else {
+        info!("Tracking unique DOIs/persons/affiliations with constant-memory HyperLogLog estimates (--estimate-counts)");
+    }
+    info!("Statistics will be logged every {} seconds", stats_interval);
+
+    if let Some(provider) = &filter_provider {
+        info!("Filtering by provider ID: {}", provider);
+    }
+
+    if let Some(client) = &filter_client {
+        info!("Filtering by client ID: {}", client);
+    }
+
+    if organize_by_provider {
+        info!("Output will be organized by provider/client in directory: {}", output);
+    } else {
+        info!("Output will be written to single file: {}", output);
+    }
+
+    if let Some(threads_str) = matches.value_of("threads") {
+        if let Ok(threads) = threads_str.parse::<usize>() {
+            if threads > 0 {
+                rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+                info!("Using {} threads", threads);
+            }
+        }
+    }
+
+    let options = ProcessDirectoryOptions {
+        input,
+        output,
+        batch_size,
+        target_batch_bytes,
+        stats_interval,
+        organize_by_provider,
+        filter_provider,
+        filter_client,
+        max_open_files,
+        max_distinct_memory_bytes,
+        checkpoint_enabled,
+        exact_counts,
+        output_format,
+        channel_capacity,
+        high_water_ratio,
+        low_water_ratio,
+        rest_endpoint,
+        rest_editgroup,
+        rest_auto_accept,
+        rest_dry_run,
+        rest_max_retries,
+        rest_retry_base,
+    };
+
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || {
+            info!("Received interrupt signal; finishing in-flight work and flushing output before exiting...");
+            stop.store(true, Ordering::Relaxed);
+        })
+        .context("Failed to register Ctrl-C handler")?;
+    }
+
+    let (progress_tx, progress_rx) = unbounded::<ProgressData>();
+    let progress_thread = std::thread::spawn(move || {
+        let progress_bar = ProgressBar::new(0);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        progress_bar.set_message("Discovering files...");
+        for update in progress_rx {
+            match update.stage {
+                ProgressStage::Discovering => {
+                    progress_bar.set_message("Discovering files...");
+                }
+                ProgressStage::Processing => {
+                    if progress_bar.length() != Some(update.files_to_check as u64) {
+                        progress_bar.set_length(update.files_to_check as u64);
+                    }
+                    progress_bar.set_position(update.files_checked as u64);
+                    progress_bar.set_message(format!("{} records processed", update.records_processed));
+                }
+                ProgressStage::Finalizing => {
+                    progress_bar.set_position(update.files_checked as u64);
+                    progress_bar.set_message("Finalizing...");
+                }
+            }
+        }
+        progress_bar.finish_with_message(format!("Completed in {}", format_elapsed(start_time.elapsed())));
+    });
+
+    let summary = process_directory(options, stop, progress_tx)?;
+
+    if let Err(e) = progress_thread.join() {
+        error!("Error joining progress thread: {:?}", e);
+    }
+
+    if summary.cancelled {
+        info!(
+            "Run cancelled after {}/{} files; output has been flushed up to that point.",
+            summary.files_processed, summary.total_files
+        );
+    }
+
     let total_runtime = start_time.elapsed();
     info!("Total execution time: {}", format_elapsed(total_runtime));
-    
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tempfile::tempdir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_record(doi: &str) -> String {
+        format!(
+            r#"{{"id":"{doi}","relationships":{{"provider":{{"data":{{"id":"prov1"}}}},"client":{{"data":{{"id":"client1"}}}}}},"attributes":{{"doi":"{doi}","creators":[{{"name":"Doe, Jane","affiliation":[{{"name":"Example University","affiliationIdentifier":"https://ror.org/00000000","affiliationIdentifierScheme":"ROR"}}]}}],"contributors":[]}}}}"#,
+        )
+    }
+
+    fn create_test_jsonl_gz(dir: &Path, filename: &str, lines: &[String]) -> PathBuf {
+        let file_path = dir.join(filename);
+        let file = File::create(&file_path).unwrap();
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        for line in lines {
+            writeln!(encoder, "{}", line).unwrap();
+        }
+        encoder.finish().unwrap();
+        file_path
+    }
+
+    /// Runs the parser with `FAILPOINTS` set to arm `armed`, single-threaded
+    /// (`--threads 1`) so the injected failure happens deterministically on
+    /// the one file/batch rather than racing other workers.
+    fn run_with_failpoint(input_dir: &Path, output_file: &Path, armed: &str) -> std::process::Output {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--features",
+                "failpoints",
+                "--",
+                "-i",
+                input_dir.to_str().unwrap(),
+                "-o",
+                output_file.to_str().unwrap(),
+                "-t",
+                "1",
+                "-b",
+                "1",
+            ])
+            .env("FAILPOINTS", armed)
+            .output()
+            .expect("failed to run fast-affiliation-parser")
+    }
+
+    #[test]
+    fn write_batch_failure_is_logged_and_run_still_completes() {
+        let temp_dir = tempdir().unwrap();
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_test_jsonl_gz(&input_dir, "a.jsonl.gz", &[sample_record("10.1/a")]);
+        let output_file = temp_dir.path().join("output.csv");
+
+        let output = run_with_failpoint(&input_dir, &output_file, "write_batch_after=error");
+
+        assert!(output.status.success(), "run should still exit cleanly after a logged write_batch failure");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("write_batch_after"), "expected the armed failpoint to be logged: {stderr}");
+    }
+
+    #[test]
+    fn flush_all_failure_does_not_prevent_final_statistics() {
+        let temp_dir = tempdir().unwrap();
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_test_jsonl_gz(&input_dir, "a.jsonl.gz", &[sample_record("10.1/a")]);
+        let output_file = temp_dir.path().join("output.csv");
+
+        let output = run_with_failpoint(&input_dir, &output_file, "flush_all=error");
+
+        assert!(output.status.success(), "a failed flush_all should be logged, not fatal");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Final Statistics"), "final stats block should still print: {stderr}");
+    }
+
+    #[test]
+    fn process_jsonl_file_failure_is_logged_and_skipped() {
+        let temp_dir = tempdir().unwrap();
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_test_jsonl_gz(&input_dir, "a.jsonl.gz", &[sample_record("10.1/a")]);
+        create_test_jsonl_gz(&input_dir, "b.jsonl.gz", &[sample_record("10.1/b")]);
+        let output_file = temp_dir.path().join("output.csv");
+
+        let output = run_with_failpoint(&input_dir, &output_file, "process_jsonl_file=error");
+
+        assert!(output.status.success(), "a per-file error should be logged and skipped, not abort the run");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Error processing"), "expected a per-file error to be logged: {stderr}");
+    }
+
+    #[test]
+    fn tx_send_failure_drops_the_batch_without_hanging() {
+        let temp_dir = tempdir().unwrap();
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_test_jsonl_gz(&input_dir, "a.jsonl.gz", &[sample_record("10.1/a")]);
+        let output_file = temp_dir.path().join("output.csv");
+
+        let output = run_with_failpoint(&input_dir, &output_file, "tx_send=error");
+
+        assert!(output.status.success(), "a dropped batch should be logged, and the run must still terminate");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("tx_send"), "expected the armed failpoint to be logged: {stderr}");
+    }
+
+    #[test]
+    fn armed_panic_action_aborts_the_run() {
+        let temp_dir = tempdir().unwrap();
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_test_jsonl_gz(&input_dir, "a.jsonl.gz", &[sample_record("10.1/a")]);
+        let output_file = temp_dir.path().join("output.csv");
+
+        let output = run_with_failpoint(&input_dir, &output_file, "process_jsonl_file=panic");
+
+        assert!(!output.status.success(), "a failpoint armed to panic should bring the run down");
+    }
+}
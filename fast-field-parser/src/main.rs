@@ -1,22 +1,33 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use arrow::array::{
+    BooleanArray, Float64Array, Int64Array, RecordBatch as ArrowRecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow::datatypes::{
+    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema, TimeUnit as ArrowTimeUnit,
+};
+use bzip2::read::BzDecoder;
 use clap::Parser;
 use csv::Writer;
 use flate2::read::GzDecoder;
-use glob::glob;
+use indexmap::IndexMap;
 use indicatif::{ProgressBar, ProgressStyle};
 use lazy_static::lazy_static;
 use log::{error, info, LevelFilter};
 use num_cpus;
+use parquet::arrow::ArrowWriter;
 use rayon::prelude::*;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use simple_logger::SimpleLogger;
-use crossbeam_channel::{bounded, Sender};
+use crossbeam_channel::{bounded, unbounded, Sender};
 
 
 #[derive(Parser)]
@@ -24,16 +35,16 @@ use crossbeam_channel::{bounded, Sender};
 #[command(about = "Efficiently extracts any field data from DataCite metadata in compressed JSONL files using a PatternTrie")]
 #[command(version = "1.1")]
 struct Cli {
-    #[arg(short, long, help = "Directory containing JSONL.gz files", required = true)]
+    #[arg(short, long, help = "Directory containing JSONL files (.jsonl.gz, .jsonl.bz2, .jsonl.zst, or uncompressed .jsonl/.json), or an s3://, gs://, or az:// URI", required = true)]
     input: String,
 
-    #[arg(short, long, default_value = "field_data.csv", help = "Output CSV file or directory")]
+    #[arg(short, long, default_value = "field_data.csv", help = "Output CSV file or directory, or an s3://, gs://, or az:// URI (remote output only supports --output-format csv)")]
     output: String,
 
     #[arg(short, long, default_value = "INFO", help = "Logging level (DEBUG, INFO, WARN, ERROR)")]
     log_level: String,
 
-    #[arg(short, long, default_value = "0", help = "Number of threads to use (0 for auto)")]
+    #[arg(short, long, visible_alias = "jobs", default_value = "0", help = "Number of files to process concurrently (0 for auto)")]
     threads: usize,
 
     #[arg(short, long, default_value = "5000", help = "Number of records to batch before sending to the writer thread")]
@@ -42,13 +53,13 @@ struct Cli {
     #[arg(short = 'g', long, help = "Organize output by provider/client using an LRU cache for file handles")]
     organize: bool,
 
-    #[arg(long, help = "Filter by provider ID")]
+    #[arg(long, help = "Filter by provider ID; also prunes hive-style 'provider=<id>' input directories (before they're listed for local input, after listing for remote input)")]
     provider: Option<String>,
 
-    #[arg(long, help = "Filter by client ID")]
+    #[arg(long, help = "Filter by client ID; also prunes hive-style 'client=<id>' input directories (before they're listed for local input, after listing for remote input)")]
     client: Option<String>,
-    
-    #[arg(long, help = "Comma-separated list of resource types to include (e.g., 'Dataset,Text')")]
+
+    #[arg(long, help = "Comma-separated list of resource types to include (e.g., 'Dataset,Text'); also prunes hive-style 'resourceType=<type>' input directories (before they're listed for local input, after listing for remote input)")]
     resource_types: Option<String>,
 
     #[arg(long, help = "Only include records that contain all specified top-level fields")]
@@ -71,8 +82,155 @@ struct Cli {
     #[arg(long, default_value = "100", help = "Maximum number of open files when using --organize")]
     max_open_files: usize,
 
-    #[arg(short = 'f', long, default_value = "creators.name", help = "Comma-separated list of fields to extract")]
-    fields: String,
+    #[arg(short = 'f', long, help = "Comma-separated list of fields to extract (overrides --config's field list); append '::type' (int, float, bool, timestamp|<fmt>, timestamptz|<fmt>) to convert a field's value, e.g. 'publicationYear::int'")]
+    fields: Option<String>,
+
+    #[arg(long, help = "Validate geoLocations coordinates and write a sidecar error report")]
+    validate_geo: bool,
+
+    #[arg(long, help = "Exit with a nonzero status if --validate-geo found any errors")]
+    strict: bool,
+
+    #[arg(long = "output-format", value_enum, help = "Output format for extracted field data")]
+    output_format: Option<OutputFormat>,
+
+    #[arg(long, help = "TOML or YAML file with a reusable extraction profile (field list, column aliases, filters, output format)")]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long = "on-convert-error",
+        value_enum,
+        default_value_t = OnConvertError::Raw,
+        help = "How to handle a --fields value that fails its `::type` conversion: skip (drop the row), null (emit an empty/null value), or raw (fall back to the unconverted string)"
+    )]
+    on_convert_error: OnConvertError,
+
+    #[arg(long, help = "Skip input files already recorded as complete in the '<output>.manifest.jsonl' checkpoint manifest, resuming an interrupted run")]
+    resume: bool,
+
+    #[arg(long, help = "Ignore and overwrite any existing checkpoint manifest, reprocessing every input file from scratch even with --resume")]
+    force: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OnConvertError {
+    Skip,
+    Null,
+    Raw,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct ExtractionConfig {
+    #[serde(default)]
+    fields: Vec<ConfigField>,
+    provider: Option<String>,
+    client: Option<String>,
+    output_format: Option<OutputFormat>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ConfigField {
+    path: String,
+    column: Option<String>,
+    convert: Option<String>,
+}
+
+fn load_config(path: &Path) -> Result<ExtractionConfig> {
+    let contents = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("yaml") | Some("yml") => {
+            Ok(serde_yaml::from_str(&contents)?)
+        }
+        _ => Ok(toml::from_str(&contents)?),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FieldSpec {
+    path: Vec<String>,
+    output_name: String,
+    conversion: Conversion,
+}
+
+/// A type conversion applied to a terminating field's raw string before it's
+/// stored in `FieldData`. Selected per-field via `::kind` (and `|format` for
+/// the timestamp kinds) appended to a `--fields`/config path, e.g.
+/// `dates.date::timestamp|%Y-%m-%d`, `publicationYear::int`,
+/// `geoLocationPoint.pointLatitude::float`, `isActive::bool`.
+#[derive(Debug, Clone, PartialEq)]
+enum Conversion {
+    AsIs,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp(String),
+    TimestampTz(String),
+}
+
+impl Conversion {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let (kind, arg) = spec.split_once('|').map_or((spec, None), |(k, a)| (k, Some(a)));
+        match kind {
+            "int" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" => Ok(Conversion::Boolean),
+            "timestamp" => arg
+                .map(|fmt| Conversion::Timestamp(fmt.to_string()))
+                .ok_or_else(|| "timestamp conversion requires a format, e.g. 'timestamp|%Y-%m-%d'".to_string()),
+            "timestamptz" => arg
+                .map(|fmt| Conversion::TimestampTz(fmt.to_string()))
+                .ok_or_else(|| "timestamptz conversion requires a format, e.g. 'timestamptz|%Y-%m-%dT%H:%M:%S%z'".to_string()),
+            other => Err(format!("unknown conversion '{other}' (expected int, float, bool, timestamp, or timestamptz)")),
+        }
+    }
+}
+
+fn resolve_field_specs(cli_fields: Option<&str>, config: Option<&ExtractionConfig>) -> Vec<FieldSpec> {
+    if let Some(fields_str) = cli_fields {
+        return parse_field_specifications(fields_str)
+            .into_iter()
+            .map(|(path, conversion)| {
+                let output_name = path[0].clone();
+                FieldSpec { path, output_name, conversion }
+            })
+            .collect();
+    }
+
+    if let Some(config) = config {
+        if !config.fields.is_empty() {
+            return config
+                .fields
+                .iter()
+                .map(|field| {
+                    let path: Vec<String> = field.path.split('.').map(str::trim).map(str::to_string).collect();
+                    let output_name = field.column.clone().unwrap_or_else(|| path[0].clone());
+                    let conversion = field.convert.as_deref().map_or(Conversion::AsIs, |spec| {
+                        Conversion::parse(spec).unwrap_or_else(|e| {
+                            error!("Ignoring invalid conversion on field '{}': {}", path.join("."), e);
+                            Conversion::AsIs
+                        })
+                    });
+                    FieldSpec { path, output_name, conversion }
+                })
+                .collect();
+        }
+    }
+
+    parse_field_specifications("creators.name")
+        .into_iter()
+        .map(|(path, conversion)| {
+            let output_name = path[0].clone();
+            FieldSpec { path, output_name, conversion }
+        })
+        .collect()
 }
 
 
@@ -92,7 +250,223 @@ struct FieldData {
     client_id: ClientId,
     field_name: String,
     subfield_path: String,
-    value: String,
+    value: FieldValue,
+}
+
+/// The result of applying a `Conversion` to a terminating field's raw JSON
+/// string. Carries a native Rust type so downstream output strategies can
+/// write it as a typed Arrow column (Parquet) or normalize it to text
+/// (CSV/NDJSON) without re-parsing.
+#[derive(Debug, Clone)]
+enum FieldValue {
+    Raw(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    /// Microseconds since the Unix epoch, UTC, for a `timestamp` conversion
+    /// (the source string carried no offset, so it's treated as UTC).
+    Timestamp(i64),
+    /// Microseconds since the Unix epoch, UTC, for a `timestamptz` conversion.
+    TimestampTz(i64),
+    Null,
+}
+
+impl FieldValue {
+    fn to_csv_cell(&self) -> String {
+        match self {
+            FieldValue::Raw(s) => s.clone(),
+            FieldValue::Integer(i) => i.to_string(),
+            FieldValue::Float(f) => f.to_string(),
+            FieldValue::Boolean(b) => b.to_string(),
+            FieldValue::Timestamp(micros) | FieldValue::TimestampTz(micros) => {
+                chrono::DateTime::<chrono::Utc>::from_timestamp_micros(*micros)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default()
+            }
+            FieldValue::Null => String::new(),
+        }
+    }
+
+    fn to_json_value(&self) -> Value {
+        match self {
+            FieldValue::Raw(s) => Value::String(s.clone()),
+            FieldValue::Integer(i) => Value::from(*i),
+            FieldValue::Float(f) => serde_json::Number::from_f64(*f).map(Value::Number).unwrap_or(Value::Null),
+            FieldValue::Boolean(b) => Value::Bool(*b),
+            FieldValue::Timestamp(_) | FieldValue::TimestampTz(_) => Value::String(self.to_csv_cell()),
+            FieldValue::Null => Value::Null,
+        }
+    }
+}
+
+/// Applies `conversion` to a terminating field's raw string, falling back to
+/// `on_error`'s policy when the conversion fails. `Conversion::AsIs` always
+/// succeeds, regardless of `on_error`.
+fn convert_value(raw: &str, conversion: &Conversion, on_error: OnConvertError) -> Option<FieldValue> {
+    let converted = match conversion {
+        Conversion::AsIs => return Some(FieldValue::Raw(raw.to_string())),
+        Conversion::Integer => raw.trim().parse::<i64>().ok().map(FieldValue::Integer),
+        Conversion::Float => raw.trim().parse::<f64>().ok().map(FieldValue::Float),
+        Conversion::Boolean => match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(FieldValue::Boolean(true)),
+            "false" | "0" | "no" => Some(FieldValue::Boolean(false)),
+            _ => None,
+        },
+        Conversion::Timestamp(fmt) => parse_naive_timestamp(raw, fmt).map(FieldValue::Timestamp),
+        Conversion::TimestampTz(fmt) => parse_tz_timestamp(raw, fmt).map(FieldValue::TimestampTz),
+    };
+
+    match converted {
+        Some(value) => Some(value),
+        None => match on_error {
+            OnConvertError::Skip => None,
+            OnConvertError::Null => Some(FieldValue::Null),
+            OnConvertError::Raw => Some(FieldValue::Raw(raw.to_string())),
+        },
+    }
+}
+
+fn parse_naive_timestamp(raw: &str, fmt: &str) -> Option<i64> {
+    chrono::NaiveDateTime::parse_from_str(raw, fmt)
+        .ok()
+        .or_else(|| chrono::NaiveDate::parse_from_str(raw, fmt).ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+        .map(|naive| naive.and_utc().timestamp_micros())
+}
+
+fn parse_tz_timestamp(raw: &str, fmt: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_str(raw, fmt)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc).timestamp_micros())
+}
+
+#[derive(Debug, Clone)]
+enum GeoError {
+    MissingLatitude { doi: String },
+    MissingLongitude { doi: String },
+    BadLatitude { doi: String, value: String },
+    BadLongitude { doi: String, value: String },
+    BadBox { doi: String, side: String },
+}
+
+impl GeoError {
+    fn doi(&self) -> &str {
+        match self {
+            GeoError::MissingLatitude { doi }
+            | GeoError::MissingLongitude { doi }
+            | GeoError::BadLatitude { doi, .. }
+            | GeoError::BadLongitude { doi, .. }
+            | GeoError::BadBox { doi, .. } => doi,
+        }
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            GeoError::MissingLatitude { .. } | GeoError::BadLatitude { .. } => {
+                "geoLocationPoint.pointLatitude"
+            }
+            GeoError::MissingLongitude { .. } | GeoError::BadLongitude { .. } => {
+                "geoLocationPoint.pointLongitude"
+            }
+            GeoError::BadBox { .. } => "geoLocationBox",
+        }
+    }
+
+    fn reason(&self) -> String {
+        match self {
+            GeoError::MissingLatitude { .. } => "missing pointLatitude".to_string(),
+            GeoError::MissingLongitude { .. } => "missing pointLongitude".to_string(),
+            GeoError::BadLatitude { .. } => "latitude not a float in [-90, 90]".to_string(),
+            GeoError::BadLongitude { .. } => "longitude not a float in [-180, 180]".to_string(),
+            GeoError::BadBox { side, .. } => format!("invalid or out-of-range {}", side),
+        }
+    }
+
+    fn offending_value(&self) -> String {
+        match self {
+            GeoError::BadLatitude { value, .. } | GeoError::BadLongitude { value, .. } => {
+                value.clone()
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+fn value_as_coord_str(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn validate_geo_locations(doi: &str, attributes: &Value, errors: &mut Vec<GeoError>) {
+    let Some(geo_locations) = attributes.pointer("/geoLocations").and_then(Value::as_array) else {
+        return;
+    };
+
+    for geo_location in geo_locations {
+        if let Some(point) = geo_location.get("geoLocationPoint") {
+            let lat = point.get("pointLatitude");
+            let lon = point.get("pointLongitude");
+
+            match lat.and_then(value_as_coord_str) {
+                None => errors.push(GeoError::MissingLatitude { doi: doi.to_string() }),
+                Some(value) => match value.parse::<f64>() {
+                    Ok(parsed) if (-90.0..=90.0).contains(&parsed) => {}
+                    _ => errors.push(GeoError::BadLatitude { doi: doi.to_string(), value }),
+                },
+            }
+
+            match lon.and_then(value_as_coord_str) {
+                None => errors.push(GeoError::MissingLongitude { doi: doi.to_string() }),
+                Some(value) => match value.parse::<f64>() {
+                    Ok(parsed) if (-180.0..=180.0).contains(&parsed) => {}
+                    _ => errors.push(GeoError::BadLongitude { doi: doi.to_string(), value }),
+                },
+            }
+        }
+
+        if let Some(geo_box) = geo_location.get("geoLocationBox") {
+            let south = geo_box.get("southBoundLatitude").and_then(value_as_coord_str).and_then(|v| v.parse::<f64>().ok());
+            let north = geo_box.get("northBoundLatitude").and_then(value_as_coord_str).and_then(|v| v.parse::<f64>().ok());
+            let west = geo_box.get("westBoundLongitude").and_then(value_as_coord_str).and_then(|v| v.parse::<f64>().ok());
+            let east = geo_box.get("eastBoundLongitude").and_then(value_as_coord_str).and_then(|v| v.parse::<f64>().ok());
+
+            let valid = match (south, north, west, east) {
+                (Some(s), Some(n), Some(w), Some(e)) => {
+                    (-90.0..=90.0).contains(&s)
+                        && (-90.0..=90.0).contains(&n)
+                        && (-180.0..=180.0).contains(&w)
+                        && (-180.0..=180.0).contains(&e)
+                        && s <= n
+                        && w <= e
+                }
+                _ => false,
+            };
+
+            if !valid {
+                let side = match (south, north, west, east) {
+                    (None, _, _, _) => "southBoundLatitude",
+                    (_, None, _, _) => "northBoundLatitude",
+                    (_, _, None, _) => "westBoundLongitude",
+                    (_, _, _, None) => "eastBoundLongitude",
+                    (Some(s), Some(n), _, _) if s > n => "southBoundLatitude>northBoundLatitude",
+                    _ => "westBoundLongitude>eastBoundLongitude",
+                };
+                errors.push(GeoError::BadBox { doi: doi.to_string(), side: side.to_string() });
+            }
+        }
+    }
+}
+
+fn write_geo_errors(path: &Path, errors: &[GeoError]) -> Result<()> {
+    let mut writer = Writer::from_path(path)?;
+    writer.write_record(&["doi", "field", "reason", "offending_value"])?;
+    for error in errors {
+        writer.write_record(&[error.doi(), error.field(), &error.reason(), &error.offending_value()])?;
+    }
+    writer.flush()?;
+    Ok(())
 }
 
 const ARRAY_TRAVERSAL_KEY: &str = "[]";
@@ -118,23 +492,30 @@ lazy_static! {
     };
 }
 
+#[derive(Debug, Clone)]
+struct TerminatingField {
+    output_name: String,
+    conversion: Conversion,
+}
+
 #[derive(Default, Debug)]
 struct TrieNode {
     children: HashMap<String, TrieNode>,
-    terminating_pattern: Option<String>,
+    terminating_pattern: Option<TerminatingField>,
 }
 
 #[derive(Debug)]
 struct PatternTrie {
     root: TrieNode,
+    on_convert_error: OnConvertError,
 }
 
 impl PatternTrie {
-     fn new(field_specs: &[Vec<String>]) -> Self {
+     fn new(field_specs: &[FieldSpec], on_convert_error: OnConvertError) -> Self {
         let mut root = TrieNode::default();
         for spec in field_specs {
             let mut current_node = &mut root;
-            let full_path = spec;
+            let full_path = &spec.path;
             let mut current_path_parts: Vec<&str> = Vec::new();
 
             for part in full_path {
@@ -145,9 +526,12 @@ impl PatternTrie {
                     current_node = current_node.children.entry(ARRAY_TRAVERSAL_KEY.to_string()).or_default();
                 }
             }
-            current_node.terminating_pattern = Some(spec[0].clone());
+            current_node.terminating_pattern = Some(TerminatingField {
+                output_name: spec.output_name.clone(),
+                conversion: spec.conversion.clone(),
+            });
         }
-        Self { root }
+        Self { root, on_convert_error }
     }
 
     fn extract(&self, json_attributes: &Value, doi: Doi, provider_id: ProviderId, client_id: ClientId) -> Vec<FieldData> {
@@ -157,15 +541,17 @@ impl PatternTrie {
     }
 
     fn traverse<'a>( &self, node: &'a TrieNode, json_value: &'a Value, current_path: &str, results: &mut Vec<FieldData>, doi: &Doi, provider_id: &ProviderId, client_id: &ClientId) {
-        if let Some(field_name) = &node.terminating_pattern {
-            let value_str = match json_value {
+        if let Some(terminating_field) = &node.terminating_pattern {
+            let raw_value = match json_value {
                 Value::String(s) => s.clone(),
                 other => other.to_string(),
             };
-            results.push(FieldData {
-                doi: doi.clone(), provider_id: provider_id.clone(), client_id: client_id.clone(),
-                field_name: field_name.clone(), subfield_path: current_path.to_string(), value: value_str,
-            });
+            if let Some(value) = convert_value(&raw_value, &terminating_field.conversion, self.on_convert_error) {
+                results.push(FieldData {
+                    doi: doi.clone(), provider_id: provider_id.clone(), client_id: client_id.clone(),
+                    field_name: terminating_field.output_name.clone(), subfield_path: current_path.to_string(), value,
+                });
+            }
         }
         for (key, child_node) in &node.children {
             if key == ARRAY_TRAVERSAL_KEY {
@@ -184,8 +570,25 @@ impl PatternTrie {
 }
 
 
-fn parse_field_specifications(field_specs: &str) -> Vec<Vec<String>> {
-    field_specs.split(',').map(|spec| spec.trim().split('.').map(|part| part.trim().to_string()).collect()).collect()
+fn parse_field_specifications(field_specs: &str) -> Vec<(Vec<String>, Conversion)> {
+    field_specs
+        .split(',')
+        .map(|spec| {
+            let spec = spec.trim();
+            let (path_str, conversion) = match spec.split_once("::") {
+                Some((path, conv)) => match Conversion::parse(conv.trim()) {
+                    Ok(conversion) => (path.trim(), conversion),
+                    Err(e) => {
+                        error!("Ignoring invalid conversion on field '{}': {}", path.trim(), e);
+                        (path.trim(), Conversion::AsIs)
+                    }
+                },
+                None => (spec, Conversion::AsIs),
+            };
+            let path = path_str.split('.').map(|part| part.trim().to_string()).collect();
+            (path, conversion)
+        })
+        .collect()
 }
 
 fn validate_field_value(attributes_val: &Value, path_parts: &[String], required_value: &str) -> bool {
@@ -244,14 +647,467 @@ fn path_exists(attributes_val: &Value, path_parts: &[String]) -> bool {
 }
 
 
-fn find_jsonl_gz_files<P: AsRef<Path>>(directory: P) -> Result<Vec<PathBuf>> {
-    let pattern = directory.as_ref().join("**/*.jsonl.gz");
-    info!("Searching for files matching pattern: {}", pattern.to_string_lossy());
-    Ok(glob(&pattern.to_string_lossy())?.filter_map(Result::ok).collect())
+const INPUT_FILE_EXTENSIONS: [&str; 5] = [".jsonl.gz", ".jsonl.bz2", ".jsonl.zst", ".jsonl", ".json"];
+
+fn is_input_file_name(name: &str) -> bool {
+    INPUT_FILE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// A Hive-style `key=value` directory segment, e.g. `provider=datacite` or
+/// `resourceType=Dataset`. Returns `None` for directories that carry no
+/// partition hint, so their subtree is never pruned.
+fn parse_partition_segment(name: &str) -> Option<(&str, &str)> {
+    name.split_once('=').filter(|(key, value)| !key.is_empty() && !value.is_empty())
+}
+
+/// True if a `key=value` partition directory cannot possibly contain files
+/// matching the active `--provider`/`--client`/`--resource-types` filters,
+/// so its subtree can be skipped without ever being listed. Keys other
+/// than those three are left alone, since we have no filter to prune them
+/// against.
+fn partition_is_pruned(
+    key: &str,
+    value: &str,
+    filter_provider: Option<&str>,
+    filter_client: Option<&str>,
+    filter_resource_types: Option<&HashSet<String>>,
+) -> bool {
+    match key {
+        "provider" => filter_provider.is_some_and(|p| p != value),
+        "client" => filter_client.is_some_and(|c| c != value),
+        "resourceType" => filter_resource_types.is_some_and(|types| !types.contains(value)),
+        _ => false,
+    }
+}
+
+/// Enumerates `directory` level by level instead of one flat recursive
+/// glob, so that a Hive-style partition subtree failing `--provider`,
+/// `--client`, or `--resource-types` is pruned before it's ever listed
+/// rather than read and discarded afterward by `JsonlProcessor`. Each
+/// level's directories are listed concurrently, bounded by the global
+/// Rayon pool set up in `main`. A directory whose name carries no
+/// recognized partition key is always descended into; the files under it
+/// fall back to being read (and filtered) normally.
+fn find_input_files<P: AsRef<Path>>(
+    directory: P,
+    filter_provider: Option<&str>,
+    filter_client: Option<&str>,
+    filter_resource_types: Option<&HashSet<String>>,
+) -> Result<Vec<PathBuf>> {
+    let mut frontier = vec![directory.as_ref().to_path_buf()];
+    let mut files = Vec::new();
+
+    while !frontier.is_empty() {
+        let listings: Vec<Result<Vec<fs::DirEntry>>> = frontier
+            .par_iter()
+            .map(|dir| Ok(fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?))
+            .collect();
+
+        let mut next_frontier = Vec::new();
+        for listing in listings {
+            for entry in listing? {
+                let file_type = entry.file_type()?;
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+
+                if file_type.is_dir() {
+                    let pruned = parse_partition_segment(&name).is_some_and(|(key, value)| {
+                        partition_is_pruned(key, value, filter_provider, filter_client, filter_resource_types)
+                    });
+                    if pruned {
+                        info!("Pruning partition directory: {}", entry.path().to_string_lossy());
+                    } else {
+                        next_frontier.push(entry.path());
+                    }
+                } else if is_input_file_name(&name) {
+                    files.push(entry.path());
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Abstracts `--input`/`--output` over local paths and `object_store`-backed
+/// remote URIs (`s3://`, `gs://`, `az://`), so the rest of the pipeline can
+/// discover and read input files, and write output, without caring which
+/// one it's talking to.
+mod store {
+    use super::*;
+    use object_store::path::Path as ObjectPath;
+    use object_store::ObjectStore as ArrowObjectStore;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum InputKey {
+        Local(PathBuf),
+        Remote { display: String, path: ObjectPath },
+    }
+
+    impl InputKey {
+        pub fn display(&self) -> String {
+            match self {
+                InputKey::Local(path) => path.display().to_string(),
+                InputKey::Remote { display, .. } => display.clone(),
+            }
+        }
+
+        fn local_path(&self) -> Option<&Path> {
+            match self {
+                InputKey::Local(path) => Some(path.as_path()),
+                InputKey::Remote { .. } => None,
+            }
+        }
+    }
+
+    pub trait Store: Send + Sync {
+        fn list(&self) -> Result<Vec<InputKey>, String>;
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>>;
+        /// Content hash used for `--resume` checkpointing. Local files hash
+        /// their raw (compressed) bytes without decoding, exactly as before;
+        /// a remote key is hashed from the same bytes `open` will go on to
+        /// fetch, so a `--resume` run downloads each remote object once to
+        /// check it and again to process it, rather than caching the body.
+        fn content_hash(&self, key: &InputKey) -> Result<String>;
+    }
+
+    struct LocalStore {
+        root: PathBuf,
+        filter_provider: Option<String>,
+        filter_client: Option<String>,
+        filter_resource_types: Option<HashSet<String>>,
+    }
+
+    impl Store for LocalStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            super::find_input_files(&self.root, self.filter_provider.as_deref(), self.filter_client.as_deref(), self.filter_resource_types.as_ref())
+                .map(|paths| paths.into_iter().map(InputKey::Local).collect())
+                .map_err(|e| e.to_string())
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            match key {
+                InputKey::Local(path) => super::open_decoder(path).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string())),
+                InputKey::Remote { display, .. } => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("LocalStore cannot open remote key {display}"),
+                )),
+            }
+        }
+
+        fn content_hash(&self, key: &InputKey) -> Result<String> {
+            let path = key
+                .local_path()
+                .ok_or_else(|| anyhow!("LocalStore cannot hash remote key {}", key.display()))?;
+            super::hash_file_contents(path)
+        }
+    }
+
+    /// Remote store backed by the `object_store` crate, which speaks S3, GCS,
+    /// and Azure Blob behind one `ObjectStore` trait. Credentials are
+    /// resolved via each backend's usual chain (environment, profile, or
+    /// instance metadata). Listing enumerates the whole prefix in one
+    /// paginated stream rather than a serial directory walk; Hive-style
+    /// `--provider`/`--client`/`--resource-types` filters can't prune the
+    /// listing itself the way `find_input_files` does locally, but are
+    /// still applied to each key's path segments after listing.
+    struct RemoteStore {
+        runtime: tokio::runtime::Runtime,
+        inner: Box<dyn ArrowObjectStore>,
+        prefix: ObjectPath,
+        display_root: String,
+        filter_provider: Option<String>,
+        filter_client: Option<String>,
+        filter_resource_types: Option<HashSet<String>>,
+    }
+
+    impl RemoteStore {
+        fn new(
+            uri: &str,
+            filter_provider: Option<String>,
+            filter_client: Option<String>,
+            filter_resource_types: Option<HashSet<String>>,
+        ) -> Result<Self, String> {
+            let url = url::Url::parse(uri).map_err(|e| format!("invalid input URI '{uri}': {e}"))?;
+            let (inner, prefix) = object_store::parse_url(&url)
+                .map_err(|e| format!("unsupported object store URI '{uri}': {e}"))?;
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| format!("failed to start async runtime for remote input: {e}"))?;
+            Ok(Self {
+                runtime,
+                inner,
+                prefix,
+                display_root: uri.trim_end_matches('/').to_string(),
+                filter_provider,
+                filter_client,
+                filter_resource_types,
+            })
+        }
+
+        fn partition_pruned(&self, path: &ObjectPath) -> bool {
+            path.parts().any(|part| {
+                super::parse_partition_segment(part.as_ref()).is_some_and(|(key, value)| {
+                    super::partition_is_pruned(key, value, self.filter_provider.as_deref(), self.filter_client.as_deref(), self.filter_resource_types.as_ref())
+                })
+            })
+        }
+    }
+
+    impl Store for RemoteStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            let prefix = self.prefix.clone();
+            let entries: Vec<_> = self.runtime.block_on(async {
+                use futures::TryStreamExt;
+                self.inner.list(Some(&prefix)).try_collect().await.map_err(|e| e.to_string())
+            })?;
+            Ok(entries
+                .into_iter()
+                .map(|meta| meta.location)
+                .filter(|path| super::is_input_file_name(path.as_ref()))
+                .filter(|path| !self.partition_pruned(path))
+                .map(|path| InputKey::Remote {
+                    display: format!("{}/{}", self.display_root, path),
+                    path,
+                })
+                .collect())
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            let path = match key {
+                InputKey::Remote { path, .. } => path.clone(),
+                InputKey::Local(local_path) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("RemoteStore cannot open local path {}", local_path.display()),
+                    ))
+                }
+            };
+            let bytes = self
+                .runtime
+                .block_on(async { self.inner.get(&path).await?.bytes().await })
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+            let cursor = std::io::Cursor::new(bytes.to_vec());
+            let name = path.as_ref();
+            if name.ends_with(".gz") {
+                Ok(Box::new(BufReader::new(GzDecoder::new(cursor))))
+            } else if name.ends_with(".bz2") {
+                Ok(Box::new(BufReader::new(BzDecoder::new(cursor))))
+            } else if name.ends_with(".zst") {
+                Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::new(cursor)?)))
+            } else {
+                Ok(Box::new(BufReader::new(cursor)))
+            }
+        }
+
+        fn content_hash(&self, key: &InputKey) -> Result<String> {
+            let path = match key {
+                InputKey::Remote { path, .. } => path.clone(),
+                InputKey::Local(local_path) => return Err(anyhow!("RemoteStore cannot hash local path {}", local_path.display())),
+            };
+            let bytes = self
+                .runtime
+                .block_on(async { self.inner.get(&path).await?.bytes().await })
+                .map_err(|e| anyhow!(e.to_string()))?;
+            let mut hasher = Sha256::new();
+            hasher.update(&bytes);
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+    }
+
+    /// Picks a `Store` implementation from `--input`'s scheme: a bare path
+    /// (or `file://` URI) goes to `LocalStore`; anything else (`s3://`,
+    /// `gs://`, `az://`) goes through `object_store`.
+    pub fn for_input(
+        input: &str,
+        filter_provider: Option<&str>,
+        filter_client: Option<&str>,
+        filter_resource_types: Option<&HashSet<String>>,
+    ) -> Result<Box<dyn Store>, String> {
+        let filter_provider = filter_provider.map(String::from);
+        let filter_client = filter_client.map(String::from);
+        let filter_resource_types = filter_resource_types.cloned();
+        if let Some(path) = input.strip_prefix("file://") {
+            return Ok(Box::new(LocalStore { root: PathBuf::from(path), filter_provider, filter_client, filter_resource_types }));
+        }
+        if input.contains("://") {
+            return Ok(Box::new(RemoteStore::new(input, filter_provider, filter_client, filter_resource_types)?));
+        }
+        Ok(Box::new(LocalStore { root: PathBuf::from(input), filter_provider, filter_client, filter_resource_types }))
+    }
+
+    /// Buffers extracted rows as CSV in memory — organized per provider/client
+    /// when `--organize` is set, as one buffer otherwise — and uploads each
+    /// as a complete object via `object_store`'s `put` once the run
+    /// finishes. Remote object stores have no local append semantics, so
+    /// unlike `SingleFileOutput`/`OrganizedOutput` nothing is written
+    /// incrementally, and (like checkpointing, see `manifest_path`)
+    /// `--resume` has no existing object to append to.
+    pub(super) struct RemoteCsvOutput {
+        runtime: tokio::runtime::Runtime,
+        inner: Box<dyn ArrowObjectStore>,
+        prefix: ObjectPath,
+        display_root: String,
+        organize: bool,
+        buffers: HashMap<(ProviderId, ClientId), Writer<Vec<u8>>>,
+    }
+
+    impl RemoteCsvOutput {
+        pub(super) fn new(uri: &str, organize: bool) -> Result<Self> {
+            let url = url::Url::parse(uri).with_context(|| format!("Invalid output URI '{uri}'"))?;
+            let (inner, prefix) = object_store::parse_url(&url)
+                .with_context(|| format!("Unsupported object store URI '{uri}'"))?;
+            let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime for remote output")?;
+            Ok(Self {
+                runtime,
+                inner,
+                prefix,
+                display_root: uri.trim_end_matches('/').to_string(),
+                organize,
+                buffers: HashMap::new(),
+            })
+        }
+
+        fn key_for(&self, provider_id: &ProviderId, client_id: &ClientId) -> (ProviderId, ClientId) {
+            if self.organize {
+                (provider_id.clone(), client_id.clone())
+            } else {
+                (ProviderId(String::new()), ClientId(String::new()))
+            }
+        }
+
+        fn buffer_for(&mut self, provider_id: &ProviderId, client_id: &ClientId) -> Result<&mut Writer<Vec<u8>>> {
+            let key = self.key_for(provider_id, client_id);
+            if !self.buffers.contains_key(&key) {
+                let mut writer = Writer::from_writer(Vec::new());
+                writer.write_record(&["doi", "provider_id", "client_id", "field_name", "subfield_path", "value"])?;
+                self.buffers.insert(key.clone(), writer);
+            }
+            Ok(self.buffers.get_mut(&key).unwrap())
+        }
+
+        fn object_path(&self, provider_id: &ProviderId, client_id: &ClientId) -> ObjectPath {
+            let suffix = if self.organize {
+                format!("{}/{}.csv", provider_id.0, client_id.0)
+            } else {
+                "field_data.csv".to_string()
+            };
+            let prefix_str = self.prefix.as_ref();
+            if prefix_str.is_empty() {
+                ObjectPath::from(suffix)
+            } else {
+                ObjectPath::from(format!("{}/{}", prefix_str, suffix))
+            }
+        }
+    }
+
+    impl super::OutputStrategy for RemoteCsvOutput {
+        fn write_batch(&mut self, batch: &[super::FieldData]) -> Result<()> {
+            let mut grouped: HashMap<(ProviderId, ClientId), Vec<&super::FieldData>> = HashMap::new();
+            for data in batch {
+                grouped.entry(self.key_for(&data.provider_id, &data.client_id)).or_default().push(data);
+            }
+            for ((provider_id, client_id), records) in grouped {
+                let writer = self.buffer_for(&provider_id, &client_id)?;
+                for data in records {
+                    let value_cell = data.value.to_csv_cell();
+                    writer.write_record(&[&data.doi.0, &data.provider_id.0, &data.client_id.0, &data.field_name, &data.subfield_path, &value_cell])?;
+                }
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            let buffers = std::mem::take(&mut self.buffers);
+            for ((provider_id, client_id), writer) in buffers {
+                let bytes = writer
+                    .into_inner()
+                    .map_err(|e| anyhow!("Failed to flush CSV buffer for {}/{}: {}", provider_id.0, client_id.0, e))?;
+                let len = bytes.len();
+                let path = self.object_path(&provider_id, &client_id);
+                let display = format!("{}/{}", self.display_root, path.as_ref());
+                self.runtime
+                    .block_on(async { self.inner.put(&path, bytes.into()).await })
+                    .with_context(|| format!("Failed to upload {}", display))?;
+                info!("Uploaded {} ({} bytes)", display, len);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A completed-file record in the checkpoint manifest written alongside
+/// `--output`. One line of JSON per entry, appended once all of a file's
+/// batches have been flushed to the output writer.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ManifestEntry {
+    path: String,
+    content_hash: String,
+    status: String,
+}
+
+/// Remote (`s3://`/`gs://`/`az://`) output has no local directory to anchor
+/// a checkpoint manifest to, so checkpointing (and therefore `--resume`) is
+/// unsupported there.
+fn manifest_path(output: &str) -> Option<PathBuf> {
+    if output.contains("://") {
+        return None;
+    }
+    Some(PathBuf::from(format!("{output}.manifest.jsonl")))
+}
+
+/// Hashes a file's raw (compressed) bytes so a checkpoint manifest entry
+/// can be matched against the same input on a later `--resume` run without
+/// decompressing it.
+fn hash_file_contents(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut reader = BufReader::new(File::open(path)?);
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Loads `(path, content_hash)` pairs already marked complete in an
+/// existing checkpoint manifest, so those files can be skipped on
+/// `--resume`. Lines that don't parse (e.g. a half-written line from a
+/// crash mid-append) are silently skipped rather than failing the run.
+fn load_completed_manifest(path: &Path) -> HashSet<(String, String)> {
+    let Ok(contents) = fs::read_to_string(path) else { return HashSet::new(); };
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<ManifestEntry>(line).ok())
+        .filter(|entry| entry.status == "complete")
+        .map(|entry| (entry.path, entry.content_hash))
+        .collect()
+}
+
+fn open_decoder(filepath: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(filepath)?;
+    let file_name = filepath.to_string_lossy();
+    if file_name.ends_with(".gz") {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else if file_name.ends_with(".bz2") {
+        Ok(Box::new(BufReader::new(BzDecoder::new(file))))
+    } else if file_name.ends_with(".zst") {
+        Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::new(file)?)))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// A message on the worker→writer channel, tagged with the file's
+/// `file_index`. The writer thread doesn't read the tag to tell when a
+/// file's batches have all arrived: a single worker thread processes one
+/// file start-to-finish, so its `FileComplete` is guaranteed to follow all
+/// of that file's `Batch` messages on the channel.
+enum WriterMessage {
+    Batch { file_index: usize, data: Vec<FieldData> },
+    FileComplete { file_index: usize, path: String, content_hash: String },
 }
 
 trait FileProcessor {
-    fn process(&self, filepath: &Path, tx: Sender<Vec<FieldData>>) -> Result<(), (PathBuf, anyhow::Error)>;
+    fn process(&self, file_index: usize, display_name: &str, reader: Box<dyn BufRead>, content_hash: &str, tx: Sender<WriterMessage>) -> Result<(), (String, anyhow::Error)>;
 }
 
 struct JsonlProcessor {
@@ -263,18 +1119,16 @@ struct JsonlProcessor {
     field_value_filters: Vec<(Vec<String>, String)>,
     exclusion_filters: Vec<Vec<String>>,
     batch_size: usize,
+    geo_errors: Option<Arc<Mutex<Vec<GeoError>>>>,
+    records_processed: Arc<AtomicUsize>,
 }
 
 impl FileProcessor for JsonlProcessor {
-    fn process(&self, filepath: &Path, tx: Sender<Vec<FieldData>>) -> Result<(), (PathBuf, anyhow::Error)> {
-        let file = File::open(filepath).map_err(|e| (filepath.to_path_buf(), anyhow::Error::new(e).context("Failed to open file")))?;
-        let decoder = GzDecoder::new(file);
-        let reader = BufReader::new(decoder);
-        
+    fn process(&self, file_index: usize, display_name: &str, reader: Box<dyn BufRead>, content_hash: &str, tx: Sender<WriterMessage>) -> Result<(), (String, anyhow::Error)> {
         let mut batch = Vec::with_capacity(self.batch_size);
 
         for line in reader.lines() {
-            let line_str = line.map_err(|e| (filepath.to_path_buf(), anyhow::Error::new(e).context("Failed to read line")))?;
+            let line_str = line.map_err(|e| (display_name.to_string(), anyhow::Error::new(e).context("Failed to read line")))?;
             if line_str.trim().is_empty() { continue; }
 
             if let Ok(record) = serde_json::from_str::<Value>(&line_str) {
@@ -303,7 +1157,15 @@ impl FileProcessor for JsonlProcessor {
                 let (Some(provider_id), Some(client_id), Some(doi)) = (extract_provider_id(&record), extract_client_id(&record), extract_doi(&record)) else { continue; };
                 if self.filter_provider.as_ref().is_some_and(|p| *p != provider_id.0) { continue; }
                 if self.filter_client.as_ref().is_some_and(|c| *c != client_id.0) { continue; }
-                
+
+                if let Some(geo_errors) = &self.geo_errors {
+                    let mut record_errors = Vec::new();
+                    validate_geo_locations(&doi.0, attributes, &mut record_errors);
+                    if !record_errors.is_empty() {
+                        geo_errors.lock().unwrap().extend(record_errors);
+                    }
+                }
+
                 let mut extracted_data = self.trie.extract(attributes, doi, provider_id, client_id);
 
                 if let Some(required) = &self.required_fields {
@@ -316,12 +1178,14 @@ impl FileProcessor for JsonlProcessor {
                 }
 
                 if extracted_data.is_empty() { continue; }
-                
+
+                self.records_processed.fetch_add(1, Ordering::Relaxed);
+
                 batch.append(&mut extracted_data);
 
                 if batch.len() >= self.batch_size {
-                    if tx.send(std::mem::take(&mut batch)).is_err() {
-                        error!("Writer thread disconnected. Aborting processing for {}", filepath.display());
+                    if tx.send(WriterMessage::Batch { file_index, data: std::mem::take(&mut batch) }).is_err() {
+                        error!("Writer thread disconnected. Aborting processing for {}", display_name);
                         return Ok(());
                     }
                 }
@@ -329,11 +1193,20 @@ impl FileProcessor for JsonlProcessor {
         }
 
         if !batch.is_empty() {
-            if tx.send(batch).is_err() {
-                 error!("Writer thread disconnected. Could not send final batch for {}", filepath.display());
+            if tx.send(WriterMessage::Batch { file_index, data: batch }).is_err() {
+                 error!("Writer thread disconnected. Could not send final batch for {}", display_name);
+                 return Ok(());
             }
         }
-        
+
+        if tx.send(WriterMessage::FileComplete {
+            file_index,
+            path: display_name.to_string(),
+            content_hash: content_hash.to_string(),
+        }).is_err() {
+            error!("Writer thread disconnected. Could not checkpoint {}", display_name);
+        }
+
         Ok(())
     }
 }
@@ -356,17 +1229,23 @@ trait OutputStrategy: Send {
 
 struct SingleFileOutput { writer: Writer<File> }
 impl SingleFileOutput {
-    fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut writer = Writer::from_path(path)?;
-        writer.write_record(&["doi", "provider_id", "client_id", "field_name", "subfield_path", "value"])?;
-        writer.flush()?;
+    fn new<P: AsRef<Path>>(path: P, resume: bool) -> Result<Self> {
+        let path = path.as_ref();
+        let resuming_existing_file = resume && path.exists();
+        let file = OpenOptions::new().create(true).write(true).append(resuming_existing_file).truncate(!resuming_existing_file).open(path)?;
+        let mut writer = Writer::from_writer(file);
+        if !resuming_existing_file {
+            writer.write_record(&["doi", "provider_id", "client_id", "field_name", "subfield_path", "value"])?;
+            writer.flush()?;
+        }
         Ok(Self { writer })
     }
 }
 impl OutputStrategy for SingleFileOutput {
     fn write_batch(&mut self, batch: &[FieldData]) -> Result<()> {
         for data in batch {
-            self.writer.write_record(&[&data.doi.0, &data.provider_id.0, &data.client_id.0, &data.field_name, &data.subfield_path, &data.value])?;
+            let value_cell = data.value.to_csv_cell();
+            self.writer.write_record(&[&data.doi.0, &data.provider_id.0, &data.client_id.0, &data.field_name, &data.subfield_path, &value_cell])?;
         }
         Ok(())
     }
@@ -401,7 +1280,10 @@ impl OrganizedOutput {
             let provider_dir = self.base_output_dir.join(&provider_id.0);
             fs::create_dir_all(&provider_dir)?;
             let client_file = provider_dir.join(format!("{}.csv", client_id.0));
-            let write_header = !self.created_files.contains(&client_file);
+            // Also check the file already exists on disk, not just this run's
+            // `created_files`, so a `--resume` run appending to output from a
+            // prior run doesn't write a second header row into it.
+            let write_header = !self.created_files.contains(&client_file) && !client_file.exists();
             let file = OpenOptions::new().create(true).write(true).append(true).open(&client_file)?;
             let mut writer = Writer::from_writer(file);
             if write_header {
@@ -424,7 +1306,8 @@ impl OutputStrategy for OrganizedOutput {
         for ((provider_id, client_id), records) in grouped_records {
             let writer = self.get_writer(&provider_id, &client_id)?;
             for data in records {
-                 writer.write_record(&[&data.doi.0, &data.provider_id.0, &data.client_id.0, &data.field_name, &data.subfield_path, &data.value])?;
+                 let value_cell = data.value.to_csv_cell();
+                 writer.write_record(&[&data.doi.0, &data.provider_id.0, &data.client_id.0, &data.field_name, &data.subfield_path, &value_cell])?;
             }
         }
         Ok(())
@@ -434,10 +1317,198 @@ impl OutputStrategy for OrganizedOutput {
         Ok(())
     }
 }
+struct NdjsonOutput { writer: BufWriter<File> }
+impl NdjsonOutput {
+    fn new<P: AsRef<Path>>(path: P, resume: bool) -> Result<Self> {
+        let path = path.as_ref();
+        let resuming_existing_file = resume && path.exists();
+        let file = OpenOptions::new().create(true).write(true).append(resuming_existing_file).truncate(!resuming_existing_file).open(path)?;
+        Ok(Self { writer: BufWriter::new(file) })
+    }
+}
+impl OutputStrategy for NdjsonOutput {
+    fn write_batch(&mut self, batch: &[FieldData]) -> Result<()> {
+        let mut records: IndexMap<Doi, (ProviderId, ClientId, IndexMap<String, Vec<Value>>)> = IndexMap::new();
+        for data in batch {
+            let (_, _, fields) = records
+                .entry(data.doi.clone())
+                .or_insert_with(|| (data.provider_id.clone(), data.client_id.clone(), IndexMap::new()));
+            fields.entry(data.field_name.clone()).or_default().push(data.value.to_json_value());
+        }
+        for (doi, (provider_id, client_id, fields)) in records {
+            let mut object = serde_json::Map::new();
+            object.insert("doi".to_string(), Value::String(doi.0));
+            object.insert("provider_id".to_string(), Value::String(provider_id.0));
+            object.insert("client_id".to_string(), Value::String(client_id.0));
+            for (field_name, values) in fields {
+                object.insert(field_name, Value::Array(values));
+            }
+            serde_json::to_writer(&mut self.writer, &Value::Object(object))?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+    fn flush(&mut self) -> Result<()> { Ok(self.writer.flush()?) }
+}
+
+fn parquet_schema() -> Arc<ArrowSchema> {
+    Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("doi", ArrowDataType::Utf8, false),
+        ArrowField::new("provider_id", ArrowDataType::Utf8, false),
+        ArrowField::new("client_id", ArrowDataType::Utf8, false),
+        ArrowField::new("field_name", ArrowDataType::Utf8, false),
+        ArrowField::new("subfield_path", ArrowDataType::Utf8, false),
+        // Text form of the value, RFC3339-normalized for the timestamp
+        // conversions; always populated, regardless of `Conversion`.
+        ArrowField::new("value", ArrowDataType::Utf8, false),
+        // Populated only when the field's `Conversion` produced that type,
+        // so a converted column sits natively typed alongside the `value`
+        // text column instead of being re-parsed by Parquet consumers.
+        ArrowField::new("value_integer", ArrowDataType::Int64, true),
+        ArrowField::new("value_float", ArrowDataType::Float64, true),
+        ArrowField::new("value_boolean", ArrowDataType::Boolean, true),
+        ArrowField::new("value_timestamp", ArrowDataType::Timestamp(ArrowTimeUnit::Microsecond, None), true),
+        ArrowField::new("value_timestamp_tz", ArrowDataType::Timestamp(ArrowTimeUnit::Microsecond, Some("UTC".into())), true),
+    ]))
+}
+
+fn field_data_record_batch<'a>(
+    schema: &Arc<ArrowSchema>,
+    records: impl Iterator<Item = &'a FieldData> + Clone,
+) -> Result<ArrowRecordBatch> {
+    let doi: StringArray = records.clone().map(|d| d.doi.0.as_str()).collect();
+    let provider_id: StringArray = records.clone().map(|d| d.provider_id.0.as_str()).collect();
+    let client_id: StringArray = records.clone().map(|d| d.client_id.0.as_str()).collect();
+    let field_name: StringArray = records.clone().map(|d| d.field_name.as_str()).collect();
+    let subfield_path: StringArray = records.clone().map(|d| d.subfield_path.as_str()).collect();
+    let value: StringArray = records.clone().map(|d| d.value.to_csv_cell()).collect();
+    let value_integer: Int64Array = records
+        .clone()
+        .map(|d| match d.value { FieldValue::Integer(i) => Some(i), _ => None })
+        .collect();
+    let value_float: Float64Array = records
+        .clone()
+        .map(|d| match d.value { FieldValue::Float(f) => Some(f), _ => None })
+        .collect();
+    let value_boolean: BooleanArray = records
+        .clone()
+        .map(|d| match d.value { FieldValue::Boolean(b) => Some(b), _ => None })
+        .collect();
+    let value_timestamp: TimestampMicrosecondArray = records
+        .clone()
+        .map(|d| match d.value { FieldValue::Timestamp(micros) => Some(micros), _ => None })
+        .collect();
+    let value_timestamp_tz: TimestampMicrosecondArray = records
+        .map(|d| match d.value { FieldValue::TimestampTz(micros) => Some(micros), _ => None })
+        .collect::<TimestampMicrosecondArray>()
+        .with_timezone("UTC");
+
+    Ok(ArrowRecordBatch::try_new(
+        Arc::clone(schema),
+        vec![
+            Arc::new(doi), Arc::new(provider_id), Arc::new(client_id),
+            Arc::new(field_name), Arc::new(subfield_path), Arc::new(value),
+            Arc::new(value_integer), Arc::new(value_float), Arc::new(value_boolean),
+            Arc::new(value_timestamp), Arc::new(value_timestamp_tz),
+        ],
+    )?)
+}
+
+struct ParquetOutput {
+    writer: ArrowWriter<File>,
+    schema: Arc<ArrowSchema>,
+}
+impl ParquetOutput {
+    fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let schema = parquet_schema();
+        let file = File::create(path)?;
+        let writer = ArrowWriter::try_new(file, Arc::clone(&schema), None)?;
+        Ok(Self { writer, schema })
+    }
+}
+impl OutputStrategy for ParquetOutput {
+    fn write_batch(&mut self, batch: &[FieldData]) -> Result<()> {
+        if batch.is_empty() { return Ok(()); }
+        let record_batch = field_data_record_batch(&self.schema, batch.iter())?;
+        self.writer.write(&record_batch)?;
+        Ok(())
+    }
+    fn flush(&mut self) -> Result<()> { self.writer.flush().map_err(Into::into) }
+}
+
+struct OrganizedParquetOutput {
+    base_output_dir: PathBuf,
+    schema: Arc<ArrowSchema>,
+    writers: HashMap<(ProviderId, ClientId), ArrowWriter<File>>,
+}
+impl OrganizedParquetOutput {
+    fn new<P: AsRef<Path>>(output_path: P) -> Result<Self> {
+        let path = output_path.as_ref();
+        fs::create_dir_all(path)?;
+        info!("Created output directory: {}. Writing one Parquet file per provider/client.", path.display());
+        Ok(Self { base_output_dir: path.to_path_buf(), schema: parquet_schema(), writers: HashMap::new() })
+    }
+    fn get_writer(&mut self, provider_id: &ProviderId, client_id: &ClientId) -> Result<&mut ArrowWriter<File>> {
+        let key = (provider_id.clone(), client_id.clone());
+        if !self.writers.contains_key(&key) {
+            let provider_dir = self.base_output_dir.join(&provider_id.0);
+            fs::create_dir_all(&provider_dir)?;
+            let client_file = provider_dir.join(format!("{}.parquet", client_id.0));
+            let file = File::create(&client_file)?;
+            let writer = ArrowWriter::try_new(file, Arc::clone(&self.schema), None)?;
+            self.writers.insert(key.clone(), writer);
+        }
+        Ok(self.writers.get_mut(&key).unwrap())
+    }
+}
+impl OutputStrategy for OrganizedParquetOutput {
+    fn write_batch(&mut self, batch: &[FieldData]) -> Result<()> {
+        let mut grouped_records: HashMap<(ProviderId, ClientId), Vec<&FieldData>> = HashMap::new();
+        for data in batch {
+            grouped_records.entry((data.provider_id.clone(), data.client_id.clone())).or_default().push(data);
+        }
+        for ((provider_id, client_id), records) in grouped_records {
+            let schema = Arc::clone(&self.schema);
+            let record_batch = field_data_record_batch(&schema, records.iter().copied())?;
+            let writer = self.get_writer(&provider_id, &client_id)?;
+            writer.write(&record_batch)?;
+        }
+        Ok(())
+    }
+    fn flush(&mut self) -> Result<()> {
+        for (_, writer) in self.writers.iter_mut() { writer.flush()?; }
+        Ok(())
+    }
+}
+
 struct CsvWriterManager { output_strategy: Box<dyn OutputStrategy> }
 impl CsvWriterManager {
-    fn new<P: AsRef<Path>>(output_path: P, organize: bool, max_open_files: usize) -> Result<Self> {
-        let strategy: Box<dyn OutputStrategy> = if organize { Box::new(OrganizedOutput::new(output_path, max_open_files)?) } else { Box::new(SingleFileOutput::new(output_path)?) };
+    fn new<P: AsRef<Path>>(output_path: P, organize: bool, max_open_files: usize, format: OutputFormat, resume: bool) -> Result<Self> {
+        let output_str = output_path.as_ref().to_string_lossy();
+        if output_str.contains("://") && !output_str.starts_with("file://") {
+            if !matches!(format, OutputFormat::Csv) {
+                return Err(anyhow!("Remote (s3/gs/az) output only supports --output-format csv"));
+            }
+            if resume {
+                info!("--resume has no effect on remote output: an object store has no existing object to append to, so output starts from scratch.");
+            }
+            return Ok(Self { output_strategy: Box::new(store::RemoteCsvOutput::new(&output_str, organize)?) });
+        }
+        if resume && matches!(format, OutputFormat::Parquet) {
+            info!("--resume only skips already-completed input files; existing parquet output can't be appended to, so it will be rewritten from scratch.");
+        }
+        let strategy: Box<dyn OutputStrategy> = match format {
+            OutputFormat::Csv => {
+                if organize { Box::new(OrganizedOutput::new(output_path, max_open_files)?) } else { Box::new(SingleFileOutput::new(output_path, resume)?) }
+            }
+            OutputFormat::Ndjson => {
+                if organize { info!("--organize is ignored for ndjson output; writing a single file."); }
+                Box::new(NdjsonOutput::new(output_path, resume)?)
+            }
+            OutputFormat::Parquet => {
+                if organize { Box::new(OrganizedParquetOutput::new(output_path)?) } else { Box::new(ParquetOutput::new(output_path)?) }
+            }
+        };
         Ok(Self { output_strategy: strategy })
     }
     fn write_batch(&mut self, batch: &[FieldData]) -> Result<()> { self.output_strategy.write_batch(batch) }
@@ -450,6 +1521,262 @@ impl Drop for CsvWriterManager {
 }
 
 
+/// A stage of the `process_directory` pipeline, reported on its progress
+/// channel so a caller can render its own UI instead of depending on the
+/// CLI's `ProgressBar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProgressStage {
+    Discovering,
+    Hashing,
+    Processing,
+    Finalizing,
+}
+
+/// A snapshot of `process_directory`'s progress, sent on its progress
+/// channel. `files_to_check` is 0 during file discovery, the number of files
+/// found during hashing, and the pending count (after `--resume` skips) from
+/// processing on.
+#[derive(Debug, Clone)]
+struct ProgressData {
+    stage: ProgressStage,
+    files_checked: usize,
+    files_to_check: usize,
+    records_processed: usize,
+}
+
+/// Everything `process_directory` needs besides the stop flag and progress
+/// channel: the parsed CLI options plus the already-built `PatternTrie`, so
+/// a caller embedding the extractor can construct one without going
+/// through `clap` at all.
+struct ProcessDirectoryOptions {
+    input: String,
+    output: String,
+    organize: bool,
+    max_open_files: usize,
+    output_format: OutputFormat,
+    resume: bool,
+    force: bool,
+    batch_size: usize,
+    filter_provider: Option<String>,
+    filter_client: Option<String>,
+    filter_resource_types: Option<HashSet<String>>,
+    required_fields: Option<HashSet<String>>,
+    field_value_filters: Vec<(Vec<String>, String)>,
+    exclusion_filters: Vec<Vec<String>>,
+    validate_geo: bool,
+    trie: Arc<PatternTrie>,
+}
+
+/// The outcome of a `process_directory` run, including one cancelled
+/// partway through by the stop flag.
+struct ProcessSummary {
+    total_files: usize,
+    files_processed: usize,
+    records_processed: usize,
+    geo_errors: Vec<GeoError>,
+    cancelled: bool,
+}
+
+/// Finds input files, hashes them for checkpoint tracking, and extracts
+/// fields from every one not already completed, writing output as it goes.
+/// Progress is reported on `progress_tx` rather than drawn directly, so
+/// both the CLI and any embedding caller can drive the same run; `stop` is
+/// polled between files in the Rayon loop so a caller can request a clean
+/// early return (already-buffered output is still flushed and the
+/// checkpoint manifest still reflects every file that did complete).
+fn process_directory(
+    options: ProcessDirectoryOptions,
+    stop: Arc<AtomicBool>,
+    progress_tx: Sender<ProgressData>,
+) -> Result<ProcessSummary> {
+    let send_progress = |stage: ProgressStage, files_checked: usize, files_to_check: usize, records_processed: usize| {
+        let _ = progress_tx.send(ProgressData { stage, files_checked, files_to_check, records_processed });
+    };
+
+    send_progress(ProgressStage::Discovering, 0, 0, 0);
+    info!("Finding files in {}...", options.input);
+    let input_store: Arc<dyn store::Store> = Arc::from(
+        store::for_input(
+            &options.input,
+            options.filter_provider.as_deref(),
+            options.filter_client.as_deref(),
+            options.filter_resource_types.as_ref(),
+        )
+        .map_err(|e| anyhow!(e))?,
+    );
+    let keys = input_store.list().map_err(|e| anyhow!(e))?;
+    info!("Found {} files.", keys.len());
+    if keys.is_empty() {
+        return Ok(ProcessSummary { total_files: 0, files_processed: 0, records_processed: 0, geo_errors: Vec::new(), cancelled: false });
+    }
+
+    let manifest_file_path = manifest_path(&options.output);
+    let resuming = options.resume && !options.force && manifest_file_path.is_some();
+    if options.resume && manifest_file_path.is_none() {
+        info!("--resume has no effect on remote output: an object store has no local directory to anchor a checkpoint manifest to, so this run always starts from scratch.");
+    }
+    let completed = if resuming {
+        let manifest_file_path = manifest_file_path.as_ref().unwrap();
+        let completed = load_completed_manifest(manifest_file_path);
+        info!("Resuming from {}: {} files already marked complete.", manifest_file_path.display(), completed.len());
+        completed
+    } else {
+        HashSet::new()
+    };
+
+    send_progress(ProgressStage::Hashing, 0, keys.len(), 0);
+    info!("Hashing {} input files for checkpoint tracking...", keys.len());
+    let pending: Vec<(store::InputKey, String)> = keys
+        .par_iter()
+        .map(|key| input_store.content_hash(key).map(|hash| (key.clone(), hash)))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(key, hash)| !completed.contains(&(key.display(), hash.clone())))
+        .collect();
+
+    let skipped = keys.len() - pending.len();
+    if skipped > 0 {
+        info!("Skipping {} files already completed in a prior run (--resume).", skipped);
+    }
+    let total_files = pending.len();
+    info!("Processing {} files.", total_files);
+    if pending.is_empty() {
+        return Ok(ProcessSummary { total_files: 0, files_processed: 0, records_processed: 0, geo_errors: Vec::new(), cancelled: false });
+    }
+
+    let channel_capacity = rayon::current_num_threads() * 4;
+    info!("Using a bounded channel with capacity: {}", channel_capacity);
+    let (tx, rx) = bounded::<WriterMessage>(channel_capacity);
+
+    let csv_writer_manager = CsvWriterManager::new(&options.output, options.organize, options.max_open_files, options.output_format, resuming)?;
+    let writer_mutex = Arc::new(Mutex::new(csv_writer_manager));
+
+    let manifest_writer = match &manifest_file_path {
+        Some(manifest_file_path) => Some(
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resuming)
+                .truncate(!resuming)
+                .open(manifest_file_path)
+                .with_context(|| format!("Failed to open checkpoint manifest {}", manifest_file_path.display()))?,
+        ),
+        None => None,
+    };
+
+    let writer_thread = {
+        let writer_mutex = Arc::clone(&writer_mutex);
+        let mut manifest_writer = manifest_writer.map(BufWriter::new);
+        std::thread::spawn(move || {
+            let mut writer_manager = writer_mutex.lock().unwrap();
+            let mut batches_processed: u32 = 0;
+            const FLUSH_INTERVAL: u32 = 100;
+
+            while let Ok(message) = rx.recv() {
+                match message {
+                    WriterMessage::Batch { data, .. } => {
+                        if let Err(e) = writer_manager.write_batch(&data) {
+                            error!("Error writing batch to CSV: {}", e);
+                            continue;
+                        }
+
+                        batches_processed += 1;
+
+                        if batches_processed % FLUSH_INTERVAL == 0 {
+                            if let Err(e) = writer_manager.flush_all() {
+                                error!("Error flushing CSV buffer: {}", e);
+                            }
+                        }
+                    }
+                    WriterMessage::FileComplete { path, content_hash, .. } => {
+                        // Flush before checkpointing so a crash can never leave a
+                        // file marked complete with data that isn't on disk yet.
+                        if let Err(e) = writer_manager.flush_all() {
+                            error!("Error flushing before checkpointing {}: {}", path, e);
+                            continue;
+                        }
+                        let Some(manifest_writer) = manifest_writer.as_mut() else { continue; };
+                        let entry = ManifestEntry { path: path.clone(), content_hash, status: "complete".to_string() };
+                        match serde_json::to_string(&entry) {
+                            Ok(line) => {
+                                if let Err(e) = writeln!(manifest_writer, "{}", line).and_then(|_| manifest_writer.flush()) {
+                                    error!("Error writing checkpoint manifest entry for {}: {}", path, e);
+                                }
+                            }
+                            Err(e) => error!("Error serializing checkpoint manifest entry for {}: {}", path, e),
+                        }
+                    }
+                }
+            }
+
+            // Reached once every `tx` clone held by a worker has been dropped,
+            // whether every file finished normally or cancellation stopped new
+            // files from being picked up: either way, this is the last chance
+            // to get buffered rows onto disk before `process_directory` returns.
+            if let Err(e) = writer_manager.flush_all() {
+                error!("Error on final flush: {}", e);
+            }
+        })
+    };
+
+    let geo_errors: Option<Arc<Mutex<Vec<GeoError>>>> = if options.validate_geo {
+        info!("Validating geoLocations coordinates (--validate-geo).");
+        Some(Arc::new(Mutex::new(Vec::new())))
+    } else {
+        None
+    };
+
+    let records_processed = Arc::new(AtomicUsize::new(0));
+    let files_done = Arc::new(AtomicUsize::new(0));
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let processor = Arc::new(JsonlProcessor {
+        trie: options.trie,
+        filter_provider: options.filter_provider,
+        filter_client: options.filter_client,
+        filter_resource_types: options.filter_resource_types,
+        required_fields: options.required_fields,
+        field_value_filters: options.field_value_filters,
+        exclusion_filters: options.exclusion_filters,
+        batch_size: options.batch_size,
+        geo_errors: geo_errors.clone(),
+        records_processed: Arc::clone(&records_processed),
+    });
+
+    pending.par_iter().enumerate().for_each_with(tx.clone(), |tx_clone, (file_index, (key, content_hash))| {
+        if stop.load(Ordering::Relaxed) {
+            cancelled.store(true, Ordering::Relaxed);
+            return;
+        }
+
+        let display_name = key.display();
+        let result = match input_store.open(key) {
+            Ok(reader) => processor.process(file_index, &display_name, reader, content_hash, tx_clone.clone()),
+            Err(e) => Err((display_name, anyhow::Error::new(e).context("Failed to open file"))),
+        };
+        if let Err((path, e)) = result {
+             error!("Error processing {}: {}", path, e)
+        }
+
+        let done = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+        send_progress(ProgressStage::Processing, done, total_files, records_processed.load(Ordering::Relaxed));
+    });
+
+    drop(tx);
+    writer_thread.join().expect("CSV writer thread panicked");
+
+    let files_processed = files_done.load(Ordering::Relaxed);
+    send_progress(ProgressStage::Finalizing, files_processed, total_files, records_processed.load(Ordering::Relaxed));
+
+    Ok(ProcessSummary {
+        total_files,
+        files_processed,
+        records_processed: records_processed.load(Ordering::Relaxed),
+        geo_errors: geo_errors.map(|errors| errors.lock().unwrap().clone()).unwrap_or_default(),
+        cancelled: cancelled.load(Ordering::Relaxed),
+    })
+}
+
 fn main() -> Result<()> {
     let start_time = Instant::now();
     let cli = Cli::parse();
@@ -494,10 +1821,27 @@ fn main() -> Result<()> {
         info!("Applying field exclusion filters for: {:?}", cli.field_does_not_exist);
     }
 
-    let field_extractions = parse_field_specifications(&cli.fields);
+    let config = cli
+        .config
+        .as_ref()
+        .map(|path| load_config(path).with_context(|| format!("Failed to load config file {}", path.display())))
+        .transpose()?;
+    if let Some(path) = &cli.config {
+        info!("Loaded extraction profile from {}", path.display());
+    }
+
+    let field_extractions = resolve_field_specs(cli.fields.as_deref(), config.as_ref());
+    let field_summary: Vec<&str> = field_extractions.iter().map(|spec| spec.output_name.as_str()).collect();
+
+    let filter_provider = cli.provider.or_else(|| config.as_ref().and_then(|c| c.provider.clone()));
+    let filter_client = cli.client.or_else(|| config.as_ref().and_then(|c| c.client.clone()));
+    let output_format = cli
+        .output_format
+        .or_else(|| config.as_ref().and_then(|c| c.output_format))
+        .unwrap_or(OutputFormat::Csv);
 
     let required_fields_set: Option<HashSet<String>> = if cli.require_all_fields {
-        let set: HashSet<String> = field_extractions.iter().map(|spec| spec[0].clone()).collect();
+        let set: HashSet<String> = field_extractions.iter().map(|spec| spec.output_name.clone()).collect();
         if !set.is_empty() { Some(set) } else { None }
     } else {
         None
@@ -506,82 +1850,82 @@ fn main() -> Result<()> {
         info!("Requiring all top-level fields to be present: {:?}", fields);
     }
 
-    info!("Building PatternTrie for fields: {}", &cli.fields);
-    let trie = Arc::new(PatternTrie::new(&field_extractions));
-    
-    info!("Finding files in {}...", cli.input);
-    let files = find_jsonl_gz_files(&cli.input)?;
-    let total_files = files.len();
-    info!("Found {} files to process.", total_files);
-    if files.is_empty() { return Ok(()); }
-
-    let progress_bar = ProgressBar::new(total_files as u64);
-    progress_bar.set_style(ProgressStyle::default_bar().template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}").unwrap().progress_chars("#>-"));
-    
-    let channel_capacity = num_threads * 4;
-    info!("Using a bounded channel with capacity: {}", channel_capacity);
-    let (tx, rx) = bounded::<Vec<FieldData>>(channel_capacity);
-    
-    let csv_writer_manager = CsvWriterManager::new(&cli.output, cli.organize, cli.max_open_files)?;
-    let writer_mutex = Arc::new(Mutex::new(csv_writer_manager));
-    
-    let writer_thread = {
-        let writer_mutex = Arc::clone(&writer_mutex);
-        std::thread::spawn(move || {
-            let mut writer_manager = writer_mutex.lock().unwrap();
-            let mut batches_processed: u32 = 0;
-            const FLUSH_INTERVAL: u32 = 100;
+    info!("Building PatternTrie for fields: {:?}", field_summary);
+    let trie = Arc::new(PatternTrie::new(&field_extractions, cli.on_convert_error));
 
-            while let Ok(batch) = rx.recv() {
-                if let Err(e) = writer_manager.write_batch(&batch) {
-                    error!("Error writing batch to CSV: {}", e);
-                    continue;
-                }
-                
-                batches_processed += 1;
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || {
+            info!("Received interrupt signal; finishing in-flight files and flushing output...");
+            stop.store(true, Ordering::Relaxed);
+        }).context("Failed to register Ctrl-C handler")?;
+    }
 
-                if batches_processed % FLUSH_INTERVAL == 0 {
-                    if let Err(e) = writer_manager.flush_all() {
-                        error!("Error flushing CSV buffer: {}", e);
-                    }
+    // The CLI is just one consumer of `process_directory`'s progress channel:
+    // it renders the existing `ProgressBar` from it on a dedicated thread, but
+    // an embedding caller could consume the same `ProgressData` events (or
+    // drive `stop` itself) without touching any of this.
+    let (progress_tx, progress_rx) = unbounded::<ProgressData>();
+    let progress_thread = std::thread::spawn(move || {
+        let progress_bar = ProgressBar::new(0);
+        progress_bar.set_style(ProgressStyle::default_bar().template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}").unwrap().progress_chars("#>-"));
+        for update in progress_rx {
+            match update.stage {
+                ProgressStage::Discovering => progress_bar.set_message("Finding files..."),
+                ProgressStage::Hashing => progress_bar.set_message(format!("Hashing {} files for checkpoint tracking...", update.files_to_check)),
+                ProgressStage::Processing => {
+                    progress_bar.set_length(update.files_to_check as u64);
+                    progress_bar.set_position(update.files_checked as u64);
+                    progress_bar.set_message(format!("{} records processed", update.records_processed));
                 }
+                ProgressStage::Finalizing => progress_bar.set_message("Finishing up..."),
             }
-            
-            if let Err(e) = writer_manager.flush_all() {
-                error!("Error on final flush: {}", e);
-            }
-        })
-    };
+        }
+        progress_bar.finish_with_message("Processing complete.");
+    });
 
-    let processor = Arc::new(JsonlProcessor {
-        trie: Arc::clone(&trie),
-        filter_provider: cli.provider,
-        filter_client: cli.client,
+    let options = ProcessDirectoryOptions {
+        input: cli.input,
+        output: cli.output.clone(),
+        organize: cli.organize,
+        max_open_files: cli.max_open_files,
+        output_format,
+        resume: cli.resume,
+        force: cli.force,
+        batch_size: cli.batch_size,
+        filter_provider,
+        filter_client,
         filter_resource_types: resource_types_filter,
         required_fields: required_fields_set,
         field_value_filters,
         exclusion_filters,
-        batch_size: cli.batch_size,
-    });
+        validate_geo: cli.validate_geo,
+        trie,
+    };
+
+    let summary = process_directory(options, stop, progress_tx)?;
+    progress_thread.join().expect("Progress rendering thread panicked");
+
+    if summary.cancelled {
+        info!("Run cancelled after {}/{} files; output has been flushed up to that point.", summary.files_processed, summary.total_files);
+    }
 
-    files.par_iter().for_each_with(tx.clone(), |tx_clone, filepath| {
-        let file_name_short = filepath.file_name().unwrap_or_default().to_string_lossy();
-        progress_bar.set_message(format!("Processing: {}", file_name_short));
-        
-        if let Err((path, e)) = processor.process(filepath, tx_clone.clone()) {
-             error!("Error processing {}: {}", path.display(), e)
-        }
-        progress_bar.inc(1);
-    });
-    
-    drop(tx); 
-    writer_thread.join().expect("CSV writer thread panicked");
-    progress_bar.finish_with_message("Processing complete.");
-    
     info!("\n--- Final Report ---");
-    info!("Processed {} files.", total_files);
+    info!("Processed {} files.", summary.files_processed);
+    info!("Processed {} records with matches.", summary.records_processed);
     info!("Total execution time: {}", format_elapsed(start_time.elapsed()));
-    
+
+    if cli.validate_geo {
+        let sidecar_path = PathBuf::from(format!("{}.geo_errors.csv", cli.output));
+        write_geo_errors(&sidecar_path, &summary.geo_errors)?;
+        info!("Wrote {} geo validation errors to {}", summary.geo_errors.len(), sidecar_path.display());
+        if cli.strict && !summary.geo_errors.is_empty() {
+            error!("Exiting nonzero due to {} geo validation errors under --strict.", summary.geo_errors.len());
+            std::process::exit(1);
+        }
+    }
+
     Ok(())
 }
 
@@ -26,7 +26,35 @@ mod tests {
         let mut encoder = GzEncoder::new(file, Compression::default());
         writeln!(encoder, "{}", json_content).unwrap();
         encoder.finish().unwrap();
-        
+
+        file_path
+    }
+
+    fn create_test_jsonl_bz2(dir: &Path, filename: &str, json_content: &str) -> PathBuf {
+        let file_path = dir.join(filename);
+        let file = File::create(&file_path).unwrap();
+        let mut encoder = bzip2::write::BzEncoder::new(file, bzip2::Compression::default());
+        writeln!(encoder, "{}", json_content).unwrap();
+        encoder.finish().unwrap();
+
+        file_path
+    }
+
+    fn create_test_jsonl_zst(dir: &Path, filename: &str, json_content: &str) -> PathBuf {
+        let file_path = dir.join(filename);
+        let file = File::create(&file_path).unwrap();
+        let mut encoder = zstd::stream::write::Encoder::new(file, 0).unwrap();
+        writeln!(encoder, "{}", json_content).unwrap();
+        encoder.finish().unwrap();
+
+        file_path
+    }
+
+    fn create_test_jsonl_plain(dir: &Path, filename: &str, json_content: &str) -> PathBuf {
+        let file_path = dir.join(filename);
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, "{}", json_content).unwrap();
+
         file_path
     }
 
@@ -79,6 +107,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_extract_doi_field_across_codecs() -> Result<(), Box<dyn std::error::Error>> {
+        let json_content = get_test_json()?;
+        let expected_doi = "10.82433/b09z-4k37";
+
+        let fixtures: [(&str, fn(&Path, &str, &str) -> PathBuf); 4] = [
+            ("example.jsonl.gz", create_test_jsonl_gz),
+            ("example.jsonl.bz2", create_test_jsonl_bz2),
+            ("example.jsonl.zst", create_test_jsonl_zst),
+            ("example.jsonl", create_test_jsonl_plain),
+        ];
+
+        for (filename, make_fixture) in fixtures {
+            let temp_dir = tempdir()?;
+            let input_dir = temp_dir.path().join("input");
+            fs::create_dir_all(&input_dir)?;
+            make_fixture(&input_dir, filename, &json_content);
+
+            let output_file = temp_dir.path().join("output.csv");
+            let status = Command::new("cargo")
+                .args(&[
+                    "run",
+                    "--",
+                    "-i", input_dir.to_str().unwrap(),
+                    "-o", output_file.to_str().unwrap(),
+                    "-f", "doi"
+                ])
+                .status()?;
+
+            assert!(status.success(), "extraction failed for {}", filename);
+            assert!(output_file.exists());
+            assert!(
+                verify_field_data(&output_file, "doi", &[expected_doi]),
+                "doi extraction mismatch for {}",
+                filename
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_extract_creators_field() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = tempdir()?;
@@ -489,7 +558,295 @@ mod tests {
         
         // Verify content in organized file
         assert!(verify_field_data(&client_file, "doi", &["10.82433/b09z-4k37"]));
-        
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ndjson_output_format() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+
+        let json_content = get_test_json()?;
+        create_test_jsonl_gz(&input_dir, "example.jsonl.gz", &json_content);
+
+        let output_file = temp_dir.path().join("output.ndjson");
+
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi,creators.name",
+                "--output-format", "ndjson"
+            ])
+            .status()?;
+
+        assert!(status.success());
+        assert!(output_file.exists());
+
+        let content = fs::read_to_string(&output_file)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1, "expected one ndjson object for the single source record");
+
+        let record: serde_json::Value = serde_json::from_str(lines[0])?;
+        assert_eq!(record["doi"], "10.82433/b09z-4k37");
+        assert!(record["creators"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|v| v == "ExampleFamilyName, ExampleGivenName"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parquet_output_format() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+
+        let json_content = get_test_json()?;
+        create_test_jsonl_gz(&input_dir, "example.jsonl.gz", &json_content);
+
+        let output_file = temp_dir.path().join("output.parquet");
+
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi,creators.name",
+                "--output-format", "parquet"
+            ])
+            .status()?;
+
+        assert!(status.success());
+        assert!(output_file.exists());
+        assert!(fs::metadata(&output_file)?.len() > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_organized_parquet_output() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+
+        let json_content = get_test_json()?;
+        create_test_jsonl_gz(&input_dir, "example.jsonl.gz", &json_content);
+
+        let output_dir = temp_dir.path().join("organized_parquet_output");
+
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_dir.to_str().unwrap(),
+                "-f", "doi,creators.name",
+                "--output-format", "parquet",
+                "-g"
+            ])
+            .status()?;
+
+        assert!(status.success());
+
+        let provider_dir = output_dir.join("datacite");
+        let client_file = provider_dir.join("datacite.mwg.parquet");
+
+        assert!(provider_dir.exists(), "Provider directory wasn't created");
+        assert!(client_file.exists(), "Client file wasn't created");
+        assert!(fs::metadata(&client_file)?.len() > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_file_matches_equivalent_cli_flags() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+
+        let json_content = get_test_json()?;
+        create_test_jsonl_gz(&input_dir, "example.jsonl.gz", &json_content);
+
+        let cli_output = temp_dir.path().join("cli_output.csv");
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", cli_output.to_str().unwrap(),
+                "-f", "doi,creators.affiliation.name"
+            ])
+            .status()?;
+        assert!(status.success());
+
+        let config_path = temp_dir.path().join("profile.toml");
+        fs::write(
+            &config_path,
+            r#"
+            [[fields]]
+            path = "doi"
+
+            [[fields]]
+            path = "creators.affiliation.name"
+            column = "affiliation"
+            "#,
+        )?;
+
+        let config_output = temp_dir.path().join("config_output.csv");
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", config_output.to_str().unwrap(),
+                "--config", config_path.to_str().unwrap()
+            ])
+            .status()?;
+        assert!(status.success());
+
+        assert!(verify_field_data(&cli_output, "doi", &["10.82433/b09z-4k37"]));
+        assert!(verify_field_data(&config_output, "doi", &["10.82433/b09z-4k37"]));
+        assert!(verify_field_data(&cli_output, "creators", &["ExampleAffiliation"]));
+        assert!(verify_field_data(&config_output, "affiliation", &["ExampleAffiliation"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jobs_flag_processes_every_shard_exactly_once() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+
+        let json_content = get_test_json()?;
+        const SHARD_COUNT: usize = 6;
+        for i in 0..SHARD_COUNT {
+            create_test_jsonl_gz(&input_dir, &format!("shard-{i}.jsonl.gz"), &json_content);
+        }
+
+        let output_file = temp_dir.path().join("output.csv");
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi",
+                "--jobs", "3"
+            ])
+            .status()?;
+
+        assert!(status.success());
+        assert!(output_file.exists());
+
+        let mut reader = ReaderBuilder::new().from_path(&output_file)?;
+        let doi_rows = reader
+            .records()
+            .filter(|r| r.as_ref().map_or(false, |rec| rec.get(3) == Some("doi")))
+            .count();
+        assert_eq!(doi_rows, SHARD_COUNT, "expected one doi row per shard regardless of --jobs");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_partition_filter_prunes_nonmatching_directories() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        let json_content = get_test_json()?;
+
+        let matching_dir = input_dir.join("provider=datacite").join("client=datacite.mwg");
+        fs::create_dir_all(&matching_dir)?;
+        create_test_jsonl_gz(&matching_dir, "part-0.jsonl.gz", &json_content);
+
+        // A sibling partition that doesn't match --provider, holding a file
+        // that isn't valid gzip. If pruning didn't skip this subtree before
+        // listing/opening it, decoding it would log a per-file error.
+        let pruned_dir = input_dir.join("provider=other").join("client=other.mwg");
+        fs::create_dir_all(&pruned_dir)?;
+        fs::write(pruned_dir.join("part-0.jsonl.gz"), b"not a real gzip file")?;
+
+        let output_file = temp_dir.path().join("output.csv");
+        let output = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi",
+                "--provider", "datacite"
+            ])
+            .output()?;
+        let status = output.status;
+        let logs = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+
+        assert!(status.success(), "the non-matching provider partition should be pruned before it's opened");
+        assert!(logs.contains("Pruning partition directory"), "expected the non-matching partition to be pruned: {logs}");
+        assert!(!logs.contains("Error processing"), "the pruned partition's file should never be opened: {logs}");
+        assert!(output_file.exists());
+        assert!(verify_field_data(&output_file, "doi", &["10.82433/b09z-4k37"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_skips_already_completed_files() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let input_dir = temp_dir.path().join("input");
+        fs::create_dir_all(&input_dir)?;
+        let json_content = get_test_json()?;
+        create_test_jsonl_gz(&input_dir, "shard-0.jsonl.gz", &json_content);
+
+        let output_file = temp_dir.path().join("output.csv");
+        let manifest_file = temp_dir.path().join("output.csv.manifest.jsonl");
+
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi",
+                "--resume",
+            ])
+            .status()?;
+        assert!(status.success());
+        assert!(manifest_file.exists(), "a checkpoint manifest should be written alongside the output");
+
+        let rows_after_first_run = ReaderBuilder::new().from_path(&output_file)?.records().count();
+        assert_eq!(rows_after_first_run, 1);
+
+        // A second shard arrives, but shard-0 is untouched. Resuming should
+        // only process the new shard, appending to (not duplicating) the
+        // output from the first run.
+        create_test_jsonl_gz(&input_dir, "shard-1.jsonl.gz", &json_content);
+
+        let status = Command::new("cargo")
+            .args(&[
+                "run",
+                "--",
+                "-i", input_dir.to_str().unwrap(),
+                "-o", output_file.to_str().unwrap(),
+                "-f", "doi",
+                "--resume",
+            ])
+            .status()?;
+        assert!(status.success(), "resuming with a newly-added shard should succeed");
+
+        let rows_after_resume = ReaderBuilder::new().from_path(&output_file)?.records().count();
+        assert_eq!(rows_after_resume, 2, "shard-0 should not be reprocessed, shard-1 should be appended");
+
+        let manifest_entries = fs::read_to_string(&manifest_file)?.lines().count();
+        assert_eq!(manifest_entries, 2, "the manifest should have one completion entry per shard");
+
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,198 +1,1155 @@
+use arrow::array::{ArrayRef, StringArray};
+use arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use arrow::record_batch::RecordBatch;
+use bzip2::read::BzDecoder;
 use clap::Parser;
 use csv::{ReaderBuilder, WriterBuilder};
 use flate2::read::GzDecoder;
 use indicatif::style::TemplateError;
 use indicatif::{ProgressBar, ProgressStyle};
 use log::{error, info, warn, LevelFilter};
+use min_max_heap::MinMaxHeap;
+use parquet::arrow::ArrowWriter;
 use rayon::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
+use crossbeam_channel::{bounded, Sender};
 use std::time::{Duration, Instant};
 use thiserror::Error;
 use walkdir::WalkDir;
 
 mod memory_usage {
-    #[derive(Debug)]
+    use log::{info, warn};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use systemstat::{Platform, System};
+
+    /// A snapshot of system-wide (not per-process) memory, since the
+    /// feedback loop below cares about what's left for the OS to hand out,
+    /// not how much this process happens to be holding.
+    #[derive(Debug, Clone, Copy)]
     pub struct MemoryStats {
-        pub rss_mb: f64,
-        pub vm_size_mb: f64,
-        pub percent: f64,
+        pub total_mb: f64,
+        pub free_mb: f64,
+        pub used_mb: f64,
+        pub percent_used: f64,
     }
 
-    #[cfg(target_os = "linux")]
+    const MB: f64 = 1024.0 * 1024.0;
+
     pub fn get_memory_usage() -> Option<MemoryStats> {
-        use std::fs::read_to_string;
-
-        let pid = std::process::id();
-        let status_file = format!("/proc/{}/status", pid);
-        if let Ok(content) = read_to_string(status_file) {
-            let mut vm_rss = None;
-            let mut vm_size = None;
-            for line in content.lines() {
-                if line.starts_with("VmRSS:") {
-                    vm_rss = line
-                        .split_whitespace()
-                        .nth(1)
-                        .and_then(|s| s.parse::<f64>().ok());
-                } else if line.starts_with("VmSize:") {
-                    vm_size = line
-                        .split_whitespace()
-                        .nth(1)
-                        .and_then(|s| s.parse::<f64>().ok());
-                }
-            }
-            if let Ok(meminfo) = read_to_string("/proc/meminfo") {
-                for line in meminfo.lines() {
-                    if line.starts_with("MemTotal:") {
-                        if let Some(mem_total_kb) = line
-                            .split_whitespace()
-                            .nth(1)
-                            .and_then(|s| s.parse::<f64>().ok())
-                        {
-                            if let (Some(rss), Some(size)) = (vm_rss, vm_size) {
-                                let percent = if mem_total_kb > 0.0 {
-                                    (rss / mem_total_kb) * 100.0
-                                } else {
-                                    0.0
-                                };
-                                return Some(MemoryStats {
-                                    rss_mb: rss / 1024.0,
-                                    vm_size_mb: size / 1024.0,
-                                    percent,
-                                });
-                            }
+        let memory = System::new().memory().ok()?;
+        let used = systemstat::saturating_sub_bytes(memory.total, memory.free);
+        let total_mb = memory.total.as_u64() as f64 / MB;
+        let free_mb = memory.free.as_u64() as f64 / MB;
+        let used_mb = used.as_u64() as f64 / MB;
+        let percent_used = if total_mb > 0.0 { used_mb / total_mb } else { 0.0 };
+        Some(MemoryStats {
+            total_mb,
+            free_mb,
+            used_mb,
+            percent_used,
+        })
+    }
+
+    pub fn log_memory_usage(note: &str) {
+        if let Some(stats) = get_memory_usage() {
+            info!(
+                "System memory ({}): {:.1} MB used / {:.1} MB total ({:.1}% used, {:.1} MB free)",
+                note,
+                stats.used_mb,
+                stats.total_mb,
+                stats.percent_used * 100.0,
+                stats.free_mb
+            );
+        } else {
+            warn!("Failed to read system memory usage ({})", note);
+        }
+    }
+
+    /// Scales `base_batch_size` by total system memory so small machines
+    /// accumulate smaller in-flight batches and large machines can safely
+    /// hold bigger ones. `base_batch_size` is calibrated for an 8 GiB
+    /// baseline machine; the scale factor is clamped to keep tiny or huge
+    /// machines from landing on degenerate batch sizes.
+    pub fn adaptive_batch_size(base_batch_size: usize, total_mb: f64) -> usize {
+        const BASELINE_TOTAL_MB: f64 = 8192.0;
+        let scale = (total_mb / BASELINE_TOTAL_MB).clamp(0.25, 4.0);
+        ((base_batch_size as f64) * scale).round().max(1.0) as usize
+    }
+
+    /// Samples system memory in the background and flips a shared flag when
+    /// usage crosses `high_water`, clearing it again once usage falls back
+    /// below `low_water`. Workers poll `should_flush()` instead of sampling
+    /// memory themselves, so the syscall cost is paid once per sample
+    /// interval rather than once per record.
+    pub struct MemoryMonitor {
+        under_pressure: Arc<AtomicBool>,
+        peak_used_mb: Arc<std::sync::atomic::AtomicU64>,
+    }
+
+    impl MemoryMonitor {
+        pub fn start(high_water: f64, low_water: f64, interval: Duration) -> Self {
+            let under_pressure = Arc::new(AtomicBool::new(false));
+            let peak_used_mb = Arc::new(std::sync::atomic::AtomicU64::new(0));
+            let flag = Arc::clone(&under_pressure);
+            let peak = Arc::clone(&peak_used_mb);
+            std::thread::spawn(move || loop {
+                if let Some(stats) = get_memory_usage() {
+                    peak.fetch_max(stats.used_mb.round() as u64, Ordering::Relaxed);
+                    if stats.percent_used >= high_water {
+                        if !flag.swap(true, Ordering::Relaxed) {
+                            warn!(
+                                "Memory usage at {:.1}% (>= {:.1}% high-water mark); forcing batch flushes",
+                                stats.percent_used * 100.0,
+                                high_water * 100.0
+                            );
                         }
+                    } else if stats.percent_used <= low_water && flag.swap(false, Ordering::Relaxed) {
+                        info!(
+                            "Memory usage back to {:.1}% (<= {:.1}% low-water mark); resuming normal batching",
+                            stats.percent_used * 100.0,
+                            low_water * 100.0
+                        );
                     }
                 }
+                std::thread::sleep(interval);
+            });
+            Self {
+                under_pressure,
+                peak_used_mb,
             }
         }
-        None
+
+        pub fn should_flush(&self) -> bool {
+            self.under_pressure.load(Ordering::Relaxed)
+        }
+
+        /// Highest system-memory-used reading observed across all samples
+        /// taken so far, in MB. Used for the completion summary; not a true
+        /// peak (samples are periodic, not continuous).
+        pub fn peak_used_mb(&self) -> f64 {
+            self.peak_used_mb.load(Ordering::Relaxed) as f64
+        }
     }
+}
 
-    #[cfg(target_os = "macos")]
-    pub fn get_memory_usage() -> Option<MemoryStats> {
-        use std::process::Command;
+/// Derives good-enough defaults for batch size, channel capacity, and
+/// matcher thread count from a live `sysinfo` reading, run once before any
+/// threads are spawned. Each value is only consulted when the matching
+/// CLI flag is left at its `0` ("auto") sentinel, so an explicit
+/// `--batch-size`/`--threads`/`--channel-capacity` always wins.
+mod autotune {
+    use log::info;
+    use sysinfo::System;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct TunedParams {
+        pub batch_size: usize,
+        pub channel_capacity: usize,
+        pub threads: usize,
+    }
 
-        let pid = std::process::id();
-        let ps_output = Command::new("ps")
-            .args(&["-o", "rss=", "-p", &pid.to_string()])
-            .output()
-            .ok()?;
-        let rss_kb = String::from_utf8_lossy(&ps_output.stdout)
-            .trim()
-            .parse::<f64>()
-            .ok()?;
-        let vsz_output = Command::new("ps")
-            .args(&["-o", "vsz=", "-p", &pid.to_string()])
-            .output()
-            .ok()?;
-        let vsz_kb = String::from_utf8_lossy(&vsz_output.stdout)
-            .trim()
-            .parse::<f64>()
-            .ok()?;
-        let hw_mem_output = Command::new("sysctl")
-            .args(&["-n", "hw.memsize"])
-            .output()
-            .ok()?;
-        let total_bytes = String::from_utf8_lossy(&hw_mem_output.stdout)
-            .trim()
-            .parse::<f64>()
-            .ok()?;
-        let total_kb = total_bytes / 1024.0;
-        let percent = if total_kb > 0.0 {
-            (rss_kb / total_kb) * 100.0
+    pub fn recommend() -> TunedParams {
+        let system = System::new_all();
+
+        let available_mb = system.available_memory() as f64 / (1024.0 * 1024.0);
+        let cpus = system.cpus().len().max(1);
+        let threads = cpus.min(8);
+
+        // Smaller batches when headroom is tight so in-flight batches
+        // don't push a small machine into swap; larger batches once
+        // there's memory to spare, for fewer, cheaper writer round-trips.
+        let batch_size = if available_mb < 2048.0 {
+            2_000
+        } else if available_mb < 8192.0 {
+            10_000
         } else {
-            0.0
+            25_000
         };
+        let channel_capacity = threads * 4;
 
-        Some(MemoryStats {
-            rss_mb: rss_kb / 1024.0,
-            vm_size_mb: vsz_kb / 1024.0,
-            percent,
-        })
+        info!(
+            "Auto-tune: {:.0} MB available, {} logical CPU(s) -> batch_size={}, channel_capacity={}, threads={}",
+            available_mb, cpus, batch_size, channel_capacity, threads
+        );
+
+        TunedParams {
+            batch_size,
+            channel_capacity,
+            threads,
+        }
     }
+}
 
-    #[cfg(target_os = "windows")]
-    pub fn get_memory_usage() -> Option<MemoryStats> {
+mod fd_limit {
+    use log::{info, warn};
+
+    /// Raises the soft `RLIMIT_NOFILE` limit so opening many input files in
+    /// parallel doesn't hit `EMFILE`, especially on macOS where the default
+    /// soft limit is tiny. Non-fatal on failure: logs a warning and leaves
+    /// the existing limit in place. `requested_ceiling` further caps the
+    /// new soft limit (via `--max-open-files`); `None` means "raise as high
+    /// as the hard limit (and, on macOS, `kern.maxfilesperproc`) allows".
+    #[cfg(unix)]
+    pub fn raise_nofile_limit(requested_ceiling: Option<u64>) {
+        use rlimit::Resource;
+
+        let (soft, hard) = match Resource::NOFILE.get() {
+            Ok(limits) => limits,
+            Err(e) => {
+                warn!("Failed to read RLIMIT_NOFILE: {e}. Leaving file descriptor limit unchanged.");
+                return;
+            }
+        };
+
+        let mut ceiling = hard;
+        #[cfg(target_os = "macos")]
+        if let Some(max_per_proc) = macos_max_files_per_proc() {
+            ceiling = ceiling.min(max_per_proc);
+        }
+        if let Some(requested) = requested_ceiling {
+            ceiling = ceiling.min(requested);
+        }
+
+        if ceiling <= soft {
+            info!("RLIMIT_NOFILE already at {} (soft, hard {}); no increase needed.", soft, hard);
+            return;
+        }
+
+        match Resource::NOFILE.set(ceiling, hard) {
+            Ok(()) => info!(
+                "Raised RLIMIT_NOFILE soft limit from {} to {} (hard limit {}).",
+                soft, ceiling, hard
+            ),
+            Err(e) => warn!(
+                "Failed to raise RLIMIT_NOFILE from {} to {}: {}. Continuing with existing limit.",
+                soft, ceiling, e
+            ),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn macos_max_files_per_proc() -> Option<u64> {
         use std::process::Command;
 
-        let pid = std::process::id();
-        let wmic_output = Command::new("wmic")
-            .args(&[
-                "process",
-                "where",
-                &format!("ProcessID={}", pid),
-                "get",
-                "WorkingSetSize,VirtualSize",
-                "/format:csv",
-            ])
-            .output()
-            .ok()?;
-        let output_str = String::from_utf8_lossy(&wmic_output.stdout);
-        let lines: Vec<&str> = output_str.lines().collect();
-        if lines.len() < 2 {
-            return None;
-        }
-        let data_parts: Vec<&str> = lines[1].split(',').collect();
-        if data_parts.len() < 3 {
-            return None;
-        }
-        let virtual_bytes = data_parts[1].trim().parse::<f64>().ok()?;
-        let working_set_bytes = data_parts[2].trim().parse::<f64>().ok()?;
-
-        let mem_output = Command::new("wmic")
-            .args(&[
-                "computersystem",
-                "get",
-                "TotalPhysicalMemory",
-                "/format:value",
-            ])
+        let output = Command::new("sysctl")
+            .args(&["-n", "kern.maxfilesperproc"])
             .output()
             .ok()?;
-        let mem_str = String::from_utf8_lossy(&mem_output.stdout);
-        let total_bytes_str = mem_str.trim().strip_prefix("TotalPhysicalMemory=")?.trim();
-        let total_bytes = total_bytes_str.parse::<f64>().ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().ok()
+    }
+
+    #[cfg(not(unix))]
+    pub fn raise_nofile_limit(_requested_ceiling: Option<u64>) {
+        info!("File descriptor limit raising is only supported on Unix platforms; skipping.");
+    }
+}
+
+mod input_format {
+    use super::*;
+
+    /// A registered input format: matches files by extension and opens a decoding reader for them.
+    pub trait FileFormat: Send + Sync {
+        fn matches(&self, path: &Path) -> bool;
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn BufRead>>;
+    }
 
-        let percent = if total_bytes > 0.0 {
-             (working_set_bytes / total_bytes) * 100.0
+    struct GzFormat;
+    impl FileFormat for GzFormat {
+        fn matches(&self, path: &Path) -> bool {
+            path.to_string_lossy().ends_with(".jsonl.gz")
+        }
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+            let file = File::open(path)?;
+            Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+        }
+    }
+
+    struct Bz2Format;
+    impl FileFormat for Bz2Format {
+        fn matches(&self, path: &Path) -> bool {
+            path.to_string_lossy().ends_with(".jsonl.bz2")
+        }
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+            let file = File::open(path)?;
+            Ok(Box::new(BufReader::new(BzDecoder::new(file))))
+        }
+    }
+
+    struct ZstFormat;
+    impl FileFormat for ZstFormat {
+        fn matches(&self, path: &Path) -> bool {
+            path.to_string_lossy().ends_with(".jsonl.zst")
+        }
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+            let file = File::open(path)?;
+            Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::new(file)?)))
+        }
+    }
+
+    struct PlainFormat;
+    impl FileFormat for PlainFormat {
+        fn matches(&self, path: &Path) -> bool {
+            let name = path.to_string_lossy();
+            name.ends_with(".jsonl") || name.ends_with(".json")
+        }
+        fn open(&self, path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+            Ok(Box::new(BufReader::new(File::open(path)?)))
+        }
+    }
+
+    /// Registry of supported input formats, tried in order against each candidate path.
+    pub fn registry() -> Vec<Box<dyn FileFormat>> {
+        vec![
+            Box::new(GzFormat),
+            Box::new(Bz2Format),
+            Box::new(ZstFormat),
+            Box::new(PlainFormat),
+        ]
+    }
+
+    pub fn format_for(path: &Path) -> Option<Box<dyn FileFormat>> {
+        registry().into_iter().find(|format| format.matches(path))
+    }
+
+    pub fn open(path: &Path) -> std::io::Result<Box<dyn BufRead>> {
+        match format_for(path) {
+            Some(format) => format.open(path),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("No registered input format matches {}", path.display()),
+            )),
+        }
+    }
+
+    /// Wraps a raw byte stream in the codec implied by `name`'s extension.
+    /// Used for inputs (e.g. remote objects) that aren't opened from a local
+    /// `Path`, but whose compression still needs to be inferred from a name.
+    pub fn wrap_decoder(name: &str, raw: Box<dyn std::io::Read + Send>) -> std::io::Result<Box<dyn BufRead>> {
+        if name.ends_with(".gz") {
+            Ok(Box::new(BufReader::new(GzDecoder::new(raw))))
+        } else if name.ends_with(".bz2") {
+            Ok(Box::new(BufReader::new(BzDecoder::new(raw))))
+        } else if name.ends_with(".zst") {
+            Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::new(raw)?)))
         } else {
-            0.0
-        };
+            Ok(Box::new(BufReader::new(raw)))
+        }
+    }
 
-        Some(MemoryStats {
-            rss_mb: working_set_bytes / (1024.0 * 1024.0),
-            vm_size_mb: virtual_bytes / (1024.0 * 1024.0),
-            percent,
+    pub fn is_supported(path: &Path) -> bool {
+        format_for(path).is_some()
+    }
+
+    /// How a decoded (already decompressed) byte stream is laid out as a
+    /// sequence of `DataCiteRecord`s. Orthogonal to `FileFormat`/codec
+    /// above: a `.jsonl` and a `.json` go through the same `PlainFormat`
+    /// reader, but one is line-delimited and the other is a single JSON
+    /// document.
+    pub enum RecordShape {
+        /// One JSON object per line (`.jsonl`, `.jsonl.gz`, ...).
+        JsonLines,
+        /// A single top-level JSON array of objects (`[ {...}, {...} ]`).
+        JsonArray,
+        /// The DataCite REST API envelope (`{"data":[{"id":..,"attributes":{..}}]}`).
+        RestEnvelope,
+    }
+
+    impl RecordShape {
+        /// Picks a shape for `name` (the path or display name, extension
+        /// intact): anything stemming to `.jsonl` is always line-delimited;
+        /// everything else is sniffed by peeking the first non-whitespace
+        /// byte of the decoded stream to tell a top-level array from a
+        /// REST-envelope object, since both commonly carry a plain `.json`
+        /// extension.
+        pub fn detect(name: &str, reader: &mut dyn BufRead) -> std::io::Result<Self> {
+            let stem = name
+                .trim_end_matches(".gz")
+                .trim_end_matches(".bz2")
+                .trim_end_matches(".zst");
+            if stem.ends_with(".jsonl") {
+                return Ok(RecordShape::JsonLines);
+            }
+            let first_byte = reader
+                .fill_buf()?
+                .iter()
+                .find(|b| !b.is_ascii_whitespace())
+                .copied();
+            Ok(match first_byte {
+                Some(b'[') => RecordShape::JsonArray,
+                _ => RecordShape::RestEnvelope,
+            })
+        }
+
+        /// Reads every `DataCiteRecord` out of `reader` per this shape,
+        /// paired with a 1-based index for error messages. `JsonLines` stays
+        /// a lazy per-line iterator so full corpus dumps keep their
+        /// streaming memory profile; `JsonArray`/`RestEnvelope` are each a
+        /// single JSON document and so are parsed whole.
+        pub fn read_records(
+            &self,
+            reader: Box<dyn BufRead>,
+        ) -> Box<dyn Iterator<Item = (usize, Result<super::DataCiteRecord, String>)>> {
+            match self {
+                RecordShape::JsonLines => Box::new(reader.lines().enumerate().filter_map(
+                    |(i, line_result)| {
+                        let index = i + 1;
+                        match line_result {
+                            Ok(line) if line.trim().is_empty() => None,
+                            Ok(line) => {
+                                let preview: String = line.chars().take(100).collect();
+                                Some((
+                                    index,
+                                    serde_json::from_str::<super::DataCiteRecord>(&line)
+                                        .map_err(|e| format!("{e} (Line: {preview}...)")),
+                                ))
+                            }
+                            Err(e) => Some((index, Err(e.to_string()))),
+                        }
+                    },
+                )),
+                RecordShape::JsonArray => {
+                    let result = serde_json::from_reader::<_, Vec<super::DataCiteRecord>>(reader)
+                        .map_err(|e| e.to_string());
+                    match result {
+                        Ok(records) => {
+                            Box::new(records.into_iter().enumerate().map(|(i, r)| (i + 1, Ok(r))))
+                        }
+                        Err(e) => Box::new(std::iter::once((1, Err(e)))),
+                    }
+                }
+                RecordShape::RestEnvelope => {
+                    #[derive(serde::Deserialize)]
+                    struct Envelope {
+                        data: Vec<super::DataCiteRecord>,
+                    }
+                    let result =
+                        serde_json::from_reader::<_, Envelope>(reader).map_err(|e| e.to_string());
+                    match result {
+                        Ok(envelope) => Box::new(
+                            envelope
+                                .data
+                                .into_iter()
+                                .enumerate()
+                                .map(|(i, r)| (i + 1, Ok(r))),
+                        ),
+                        Err(e) => Box::new(std::iter::once((1, Err(e)))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+mod store {
+    use super::*;
+    use object_store::path::Path as ObjectPath;
+    use object_store::ObjectStore as ArrowObjectStore;
+
+    /// A single input available for processing: a local path on disk, or a
+    /// key inside a remote object store addressed by URI.
+    #[derive(Debug, Clone)]
+    pub enum InputKey {
+        Local(PathBuf),
+        Remote { display: String, path: ObjectPath },
+    }
+
+    impl InputKey {
+        pub fn display(&self) -> String {
+            match self {
+                InputKey::Local(path) => path.display().to_string(),
+                InputKey::Remote { display, .. } => display.clone(),
+            }
+        }
+    }
+
+    /// Where DataCite dumps are read from: local disk via `WalkDir`, or a
+    /// remote object store (`s3://`, `gs://`, `az://`, `https://`) selected
+    /// from the `--input-dir` URI scheme.
+    pub trait Store: Send + Sync {
+        fn list(&self) -> Result<Vec<InputKey>, String>;
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>>;
+    }
+
+    struct LocalStore {
+        root: PathBuf,
+    }
+
+    impl Store for LocalStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            Ok(WalkDir::new(&self.root)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| input_format::is_supported(e.path()))
+                .map(|e| InputKey::Local(e.into_path()))
+                .collect())
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            match key {
+                InputKey::Local(path) => input_format::open(path),
+                InputKey::Remote { display, .. } => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("LocalStore cannot open remote key {display}"),
+                )),
+            }
+        }
+    }
+
+    /// Remote store backed by the `object_store` crate, which speaks S3,
+    /// GCS, Azure Blob, and plain HTTP behind one `ObjectStore` trait.
+    /// Selected whenever `--input-dir` is a non-`file://` URI;
+    /// credentials are resolved via each backend's usual chain (environment,
+    /// profile, or instance metadata).
+    struct RemoteStore {
+        runtime: tokio::runtime::Runtime,
+        inner: Box<dyn ArrowObjectStore>,
+        prefix: ObjectPath,
+        display_root: String,
+    }
+
+    impl RemoteStore {
+        fn new(uri: &str) -> Result<Self, String> {
+            let url = url::Url::parse(uri).map_err(|e| format!("invalid input URI '{uri}': {e}"))?;
+            let (inner, prefix) = object_store::parse_url(&url)
+                .map_err(|e| format!("unsupported object store URI '{uri}': {e}"))?;
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| format!("failed to start async runtime for remote input: {e}"))?;
+            Ok(Self {
+                runtime,
+                inner,
+                prefix,
+                display_root: uri.trim_end_matches('/').to_string(),
+            })
+        }
+    }
+
+    impl Store for RemoteStore {
+        fn list(&self) -> Result<Vec<InputKey>, String> {
+            let prefix = self.prefix.clone();
+            self.runtime.block_on(async {
+                use futures::TryStreamExt;
+                let entries: Vec<_> = self
+                    .inner
+                    .list(Some(&prefix))
+                    .try_collect()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(entries
+                    .into_iter()
+                    .map(|meta| meta.location)
+                    .filter(|path| input_format::is_supported(Path::new(path.as_ref())))
+                    .map(|path| InputKey::Remote {
+                        display: format!("{}/{}", self.display_root, path),
+                        path,
+                    })
+                    .collect())
+            })
+        }
+
+        fn open(&self, key: &InputKey) -> std::io::Result<Box<dyn BufRead>> {
+            let (path, name) = match key {
+                InputKey::Remote { path, display } => (path.clone(), display.clone()),
+                InputKey::Local(local_path) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("RemoteStore cannot open local path {}", local_path.display()),
+                    ))
+                }
+            };
+            let bytes = self
+                .runtime
+                .block_on(async { self.inner.get(&path).await?.bytes().await })
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+            let raw: Box<dyn std::io::Read + Send> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+            input_format::wrap_decoder(&name, raw)
+        }
+    }
+
+    /// Picks a `Store` implementation from `input_dir`'s scheme: a bare path
+    /// (or `file://` URI) goes to `LocalStore`; anything else (`s3://`,
+    /// `gs://`, `az://`, `https://`) goes through `object_store`.
+    pub fn for_input_dir(input_dir: &str) -> Result<Box<dyn Store>, String> {
+        if let Some(path) = input_dir.strip_prefix("file://") {
+            return Ok(Box::new(LocalStore { root: PathBuf::from(path) }));
+        }
+        if input_dir.contains("://") {
+            return Ok(Box::new(RemoteStore::new(input_dir)?));
+        }
+        Ok(Box::new(LocalStore { root: PathBuf::from(input_dir) }))
+    }
+}
+
+mod partition {
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
+    /// Partition columns parsed from Hive-style `key=value` path segments,
+    /// e.g. `created=2023/client=foo/part-0.jsonl.gz` yields
+    /// `{"client": "foo", "created": "2023"}`.
+    pub type PartitionValues = BTreeMap<String, String>;
+
+    /// Parses every `key=value` path segment out of a file's display path.
+    pub fn parse(display_path: &str) -> PartitionValues {
+        let mut values = PartitionValues::new();
+        for segment in display_path.split('/') {
+            if let Some((key, value)) = segment.split_once('=') {
+                if !key.is_empty() && !value.is_empty() {
+                    values.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        values
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Eq,
+        Ge,
+        Le,
+        Gt,
+        Lt,
+    }
+
+    /// A single `column<op>value` clause from `--partition-filter`.
+    #[derive(Debug, Clone)]
+    pub struct Predicate {
+        column: String,
+        op: Op,
+        value: String,
+    }
+
+    /// Parses a comma-separated `--partition-filter` expression like
+    /// `created>=2022,client=foo` into per-column predicates, ANDed
+    /// together. Two-character operators are matched before `=` so
+    /// `created>=2022` isn't mistaken for the literal `created>` equaling
+    /// `=2022`.
+    pub fn parse_filter(expr: &str) -> Result<Vec<Predicate>, String> {
+        expr.split(',').map(|clause| parse_clause(clause.trim())).collect()
+    }
+
+    fn parse_clause(clause: &str) -> Result<Predicate, String> {
+        for (token, op) in [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+            ("=", Op::Eq),
+        ] {
+            if let Some((column, value)) = clause.split_once(token) {
+                if !column.is_empty() && !value.is_empty() {
+                    return Ok(Predicate {
+                        column: column.trim().to_string(),
+                        op,
+                        value: value.trim().to_string(),
+                    });
+                }
+            }
+        }
+        Err(format!(
+            "invalid partition filter clause '{clause}' (expected e.g. 'created>=2022' or 'client=foo')"
+        ))
+    }
+
+    /// True if `values` satisfies every predicate. A partition column
+    /// missing from `values` fails any predicate referencing it, so files
+    /// that don't carry the expected partitioning are pruned rather than
+    /// silently kept.
+    pub fn matches(predicates: &[Predicate], values: &PartitionValues) -> bool {
+        predicates.iter().all(|predicate| {
+            values.get(&predicate.column).is_some_and(|actual| match predicate.op {
+                Op::Eq => actual == &predicate.value,
+                Op::Ge => compare(actual, &predicate.value) != Ordering::Less,
+                Op::Le => compare(actual, &predicate.value) != Ordering::Greater,
+                Op::Gt => compare(actual, &predicate.value) == Ordering::Greater,
+                Op::Lt => compare(actual, &predicate.value) == Ordering::Less,
+            })
         })
     }
 
-    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-    pub fn get_memory_usage() -> Option<MemoryStats> {
-        None
+    /// Compares partition values numerically when both sides parse as
+    /// integers (e.g. `created` years), falling back to lexicographic
+    /// comparison for non-numeric columns like `client`.
+    fn compare(a: &str, b: &str) -> Ordering {
+        match (a.parse::<i64>(), b.parse::<i64>()) {
+            (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+            _ => a.cmp(b),
+        }
     }
+}
 
-    pub fn log_memory_usage(note: &str) {
-        use log::info;
+/// Output format for match results, selectable via `--output-format` or
+/// inferred from `--output-csv`'s extension.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+    /// A queryable Tantivy full-text index instead of a flat file;
+    /// `--output-csv` is used as the index directory.
+    Tantivy,
+}
 
-        if let Some(stats) = get_memory_usage() {
+impl OutputFormat {
+    fn from_extension(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ndjson") | Some("jsonl") => OutputFormat::Ndjson,
+            Some("parquet") | Some("parq") => OutputFormat::Parquet,
+            Some("tantivy") => OutputFormat::Tantivy,
+            _ => OutputFormat::Csv,
+        }
+    }
+}
+
+mod sink {
+    use super::*;
+
+    pub(crate) const FIXED_COLUMNS: [&str; 5] = [
+        "input_doi",
+        "datacite_record_doi",
+        "matched_relation_type",
+        "datacite_record_resource_type",
+        "datacite_record_resource_type_general",
+    ];
+
+    /// Where finished `MatchResult` batches end up. The bounded-channel
+    /// protocol in `run_threaded` is unchanged; only the sink behind the channel
+    /// varies by `--output-format`.
+    pub trait ResultSink: Send {
+        fn write_batch(&mut self, batch: &[MatchResult]) -> Result<(), AppError>;
+        fn finish(self: Box<Self>) -> Result<(), AppError>;
+    }
+
+    pub fn build(
+        output_path: &Path,
+        format: Option<OutputFormat>,
+        partition_columns: Vec<String>,
+    ) -> Result<Box<dyn ResultSink>, AppError> {
+        let format = format.unwrap_or_else(|| OutputFormat::from_extension(output_path));
+        info!("Output format: {:?}", format);
+        match format {
+            OutputFormat::Csv => Ok(Box::new(CsvSink::new(output_path, partition_columns)?)),
+            OutputFormat::Ndjson => Ok(Box::new(NdjsonSink::new(output_path, partition_columns)?)),
+            OutputFormat::Parquet => Ok(Box::new(ParquetSink::new(output_path, partition_columns)?)),
+            OutputFormat::Tantivy => Ok(Box::new(TantivySink::new(output_path, partition_columns)?)),
+        }
+    }
+
+    pub(crate) fn row_values<'a>(result: &'a MatchResult, partition_columns: &[String]) -> Vec<&'a str> {
+        let mut row: Vec<&str> = vec![
+            &result.input_doi,
+            result.datacite_record_doi.as_deref().unwrap_or(""),
+            result.matched_relation_type.as_deref().unwrap_or(""),
+            result.datacite_record_resource_type.as_deref().unwrap_or(""),
+            result
+                .datacite_record_resource_type_general
+                .as_deref()
+                .unwrap_or(""),
+        ];
+        for column in partition_columns {
+            row.push(result.partition_values.get(column).map(String::as_str).unwrap_or(""));
+        }
+        row
+    }
+
+    struct CsvSink {
+        writer: csv::Writer<BufWriter<File>>,
+        partition_columns: Vec<String>,
+    }
+
+    impl CsvSink {
+        fn new(path: &Path, partition_columns: Vec<String>) -> Result<Self, AppError> {
+            let mut writer = WriterBuilder::new()
+                .quote_style(csv::QuoteStyle::Necessary)
+                .from_writer(BufWriter::new(File::create(path)?));
+            let mut header: Vec<&str> = FIXED_COLUMNS.to_vec();
+            header.extend(partition_columns.iter().map(String::as_str));
+            writer.write_record(&header)?;
+            writer.flush()?;
+            Ok(Self { writer, partition_columns })
+        }
+    }
+
+    impl ResultSink for CsvSink {
+        fn write_batch(&mut self, batch: &[MatchResult]) -> Result<(), AppError> {
+            for result in batch {
+                self.writer.write_record(row_values(result, &self.partition_columns))?;
+            }
+            self.writer.flush()?;
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+            self.writer.flush()?;
+            Ok(())
+        }
+    }
+
+    struct NdjsonSink {
+        writer: BufWriter<File>,
+        partition_columns: Vec<String>,
+    }
+
+    impl NdjsonSink {
+        fn new(path: &Path, partition_columns: Vec<String>) -> Result<Self, AppError> {
+            Ok(Self {
+                writer: BufWriter::new(File::create(path)?),
+                partition_columns,
+            })
+        }
+    }
+
+    impl ResultSink for NdjsonSink {
+        fn write_batch(&mut self, batch: &[MatchResult]) -> Result<(), AppError> {
+            for result in batch {
+                let mut object = serde_json::Map::new();
+                let columns = FIXED_COLUMNS.iter().map(|c| c.to_string()).chain(self.partition_columns.iter().cloned());
+                for (column, value) in columns.zip(row_values(result, &self.partition_columns)) {
+                    object.insert(column, serde_json::Value::String(value.to_string()));
+                }
+                serde_json::to_writer(&mut self.writer, &serde_json::Value::Object(object))?;
+                self.writer.write_all(b"\n")?;
+            }
+            self.writer.flush()?;
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+            self.writer.flush()?;
+            Ok(())
+        }
+    }
+
+    /// Buffers rows column-wise and writes a row group to the underlying
+    /// Parquet file once `ROW_GROUP_SIZE` rows have accumulated, rather than
+    /// flushing on every batch. All columns are plain UTF-8 strings: DOIs
+    /// and relation/resource types don't benefit from a narrower type, and
+    /// keeping everything string-typed keeps the schema trivial to re-parse.
+    struct ParquetSink {
+        writer: ArrowWriter<File>,
+        schema: Arc<ArrowSchema>,
+        columns: Vec<String>,
+        buffered: Vec<Vec<String>>,
+        buffered_rows: usize,
+    }
+
+    const ROW_GROUP_SIZE: usize = 100_000;
+
+    impl ParquetSink {
+        fn new(path: &Path, partition_columns: Vec<String>) -> Result<Self, AppError> {
+            let columns: Vec<String> = FIXED_COLUMNS
+                .iter()
+                .map(|c| c.to_string())
+                .chain(partition_columns.into_iter())
+                .collect();
+            let schema = Arc::new(ArrowSchema::new(
+                columns
+                    .iter()
+                    .map(|name| ArrowField::new(name, DataType::Utf8, false))
+                    .collect::<Vec<_>>(),
+            ));
+            let file = File::create(path)?;
+            let writer = ArrowWriter::try_new(file, Arc::clone(&schema), None)?;
+            let buffered = columns.iter().map(|_| Vec::new()).collect();
+            Ok(Self {
+                writer,
+                schema,
+                columns,
+                buffered,
+                buffered_rows: 0,
+            })
+        }
+
+        fn flush_row_group(&mut self) -> Result<(), AppError> {
+            if self.buffered_rows == 0 {
+                return Ok(());
+            }
+            let arrays: Vec<ArrayRef> = self
+                .buffered
+                .iter_mut()
+                .map(|column| Arc::new(StringArray::from(std::mem::take(column))) as ArrayRef)
+                .collect();
+            let batch = RecordBatch::try_new(Arc::clone(&self.schema), arrays)?;
+            self.writer.write(&batch)?;
+            self.writer.flush()?;
+            self.buffered_rows = 0;
+            Ok(())
+        }
+    }
+
+    impl ResultSink for ParquetSink {
+        fn write_batch(&mut self, batch: &[MatchResult]) -> Result<(), AppError> {
+            let partition_columns = self.columns[FIXED_COLUMNS.len()..].to_vec();
+            for result in batch {
+                for (column, value) in self.buffered.iter_mut().zip(row_values(result, &partition_columns)) {
+                    column.push(value.to_string());
+                }
+                self.buffered_rows += 1;
+            }
+            if self.buffered_rows >= ROW_GROUP_SIZE {
+                self.flush_row_group()?;
+            }
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+            self.flush_row_group()?;
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+
+    /// Ingests matches into a queryable Tantivy full-text index instead of
+    /// a flat file. `output_path` is used as the index directory. Fields
+    /// mirror `FIXED_COLUMNS`, plus one text field per partition column.
+    struct TantivySink {
+        writer: tantivy::IndexWriter,
+        fields: TantivyFields,
+        partition_columns: Vec<String>,
+        index_path: PathBuf,
+    }
+
+    struct TantivyFields {
+        input_doi: tantivy::schema::Field,
+        datacite_record_doi: tantivy::schema::Field,
+        matched_relation_type: tantivy::schema::Field,
+        resource_type: tantivy::schema::Field,
+        resource_type_general: tantivy::schema::Field,
+        partitions: Vec<tantivy::schema::Field>,
+    }
+
+    impl TantivySink {
+        fn new(path: &Path, partition_columns: Vec<String>) -> Result<Self, AppError> {
+            use tantivy::schema::{Schema, STORED, STRING, TEXT};
+
+            std::fs::create_dir_all(path)?;
+            let mut builder = Schema::builder();
+            let fields = TantivyFields {
+                input_doi: builder.add_text_field("input_doi", STRING | STORED),
+                datacite_record_doi: builder.add_text_field("datacite_record_doi", STRING | STORED),
+                matched_relation_type: builder.add_text_field("matched_relation_type", STRING | STORED),
+                resource_type: builder.add_text_field("datacite_record_resource_type", TEXT | STORED),
+                resource_type_general: builder
+                    .add_text_field("datacite_record_resource_type_general", TEXT | STORED),
+                partitions: partition_columns
+                    .iter()
+                    .map(|column| builder.add_text_field(column, STRING | STORED))
+                    .collect(),
+            };
+            let schema = builder.build();
+            let index = tantivy::Index::create_in_dir(path, schema)?;
+
+            // Roughly `min(cpus, 8)` indexing threads, sized down from
+            // total system memory rather than a fixed arena so the
+            // indexer scales with the box it runs on while leaving ~1GB
+            // headroom for the rest of the pipeline.
+            let threads = num_cpus::get().clamp(1, 8);
+            let total_mb = super::memory_usage::get_memory_usage()
+                .map(|stats| stats.total_mb)
+                .unwrap_or(4096.0);
+            let heap_bytes =
+                (((total_mb - 1024.0).max(256.0)) * 1024.0 * 1024.0) as usize;
+            let heap_bytes = heap_bytes.max(15_000_000 * threads);
             info!(
-                "Memory usage ({}): {:.1} MB physical (RSS), {:.1} MB virtual, {:.1}% of system memory",
-                note, stats.rss_mb, stats.vm_size_mb, stats.percent
+                "Tantivy index: {} thread(s), {:.0} MB heap arena, path {}",
+                threads,
+                heap_bytes as f64 / (1024.0 * 1024.0),
+                path.display()
             );
+            let writer = index.writer_with_num_threads(threads, heap_bytes)?;
+
+            Ok(Self {
+                writer,
+                fields,
+                partition_columns,
+                index_path: path.to_path_buf(),
+            })
+        }
+    }
+
+    impl ResultSink for TantivySink {
+        fn write_batch(&mut self, batch: &[MatchResult]) -> Result<(), AppError> {
+            for result in batch {
+                let mut document = tantivy::TantivyDocument::default();
+                document.add_text(self.fields.input_doi, &result.input_doi);
+                if let Some(value) = &result.datacite_record_doi {
+                    document.add_text(self.fields.datacite_record_doi, value);
+                }
+                if let Some(value) = &result.matched_relation_type {
+                    document.add_text(self.fields.matched_relation_type, value);
+                }
+                if let Some(value) = &result.datacite_record_resource_type {
+                    document.add_text(self.fields.resource_type, value);
+                }
+                if let Some(value) = &result.datacite_record_resource_type_general {
+                    document.add_text(self.fields.resource_type_general, value);
+                }
+                for (field, column) in self.fields.partitions.iter().zip(&self.partition_columns) {
+                    if let Some(value) = result.partition_values.get(column) {
+                        document.add_text(*field, value);
+                    }
+                }
+                self.writer.add_document(document)?;
+            }
+            self.writer.commit()?;
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+            self.writer.commit()?;
+            info!("Tantivy index finalized at {}", self.index_path.display());
+            Ok(())
+        }
+    }
+}
+
+/// `--sharded-output`: bypasses the single writer thread entirely. Each
+/// Rayon worker accumulates its own matches in memory, and at the end of
+/// the run every worker's accumulated matches are sorted by
+/// `input_doi`, written as one LZ4-compressed CSV shard, and then
+/// k-way merged into a single globally DOI-sorted CSV. This trades peak
+/// memory (a shard holds everything one worker produced) for a
+/// deterministic, bisectable output and removes the writer thread as a
+/// bottleneck on very large corpora.
+mod sharded {
+    use super::*;
+    use lz4::{Decoder, EncoderBuilder};
+
+    /// One worker's accumulated matches, flushed as a single sorted,
+    /// LZ4-compressed CSV shard file.
+    pub struct Shard {
+        path: PathBuf,
+        pending: Vec<MatchResult>,
+    }
+
+    impl Shard {
+        pub fn new(dir: &Path, index: usize) -> Self {
+            Self {
+                path: dir.join(format!("shard-{:05}.csv.lz4", index)),
+                pending: Vec::new(),
+            }
+        }
+
+        pub fn extend(&mut self, results: Vec<MatchResult>) {
+            self.pending.extend(results);
+        }
+
+        /// Sorts the shard's matches by `input_doi` and writes them,
+        /// LZ4-compressed, as a CSV file. Returns `None` if the shard
+        /// received no matches (no file is written, so the merge step
+        /// can skip it).
+        pub fn finish(mut self, partition_columns: &[String]) -> Result<Option<PathBuf>, AppError> {
+            if self.pending.is_empty() {
+                return Ok(None);
+            }
+            self.pending.sort_unstable_by(|a, b| a.input_doi.cmp(&b.input_doi));
+
+            let file = File::create(&self.path)?;
+            let encoder = EncoderBuilder::new().build(file)?;
+            let mut writer = WriterBuilder::new()
+                .quote_style(csv::QuoteStyle::Necessary)
+                .from_writer(encoder);
+            for result in &self.pending {
+                writer.write_record(sink::row_values(result, partition_columns))?;
+            }
+            let encoder = writer.into_inner().map_err(|e| e.into_error())?;
+            let (_file, result) = encoder.finish();
+            result?;
+            Ok(Some(self.path.clone()))
+        }
+    }
+
+    /// A shard reader's current front record, ordered by `input_doi` so a
+    /// `MinMaxHeap` can always surface the globally-smallest pending row.
+    struct HeapEntry {
+        doi: String,
+        row: csv::StringRecord,
+        shard: usize,
+    }
+
+    impl PartialEq for HeapEntry {
+        fn eq(&self, other: &Self) -> bool {
+            self.doi == other.doi
+        }
+    }
+    impl Eq for HeapEntry {}
+    impl PartialOrd for HeapEntry {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for HeapEntry {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.doi.cmp(&other.doi)
+        }
+    }
+
+    /// K-way merges already-sorted LZ4/CSV shards into a single
+    /// DOI-sorted CSV at `output_path`.
+    pub fn merge(
+        shard_paths: &[PathBuf],
+        output_path: &Path,
+        partition_columns: &[String],
+    ) -> Result<(), AppError> {
+        let mut readers: Vec<csv::Reader<Decoder<BufReader<File>>>> = shard_paths
+            .iter()
+            .map(|path| -> Result<_, AppError> {
+                let decoder = Decoder::new(BufReader::new(File::open(path)?))?;
+                Ok(ReaderBuilder::new().has_headers(false).from_reader(decoder))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut writer = WriterBuilder::new()
+            .quote_style(csv::QuoteStyle::Necessary)
+            .from_writer(BufWriter::new(File::create(output_path)?));
+        let mut header: Vec<&str> = sink::FIXED_COLUMNS.to_vec();
+        header.extend(partition_columns.iter().map(String::as_str));
+        writer.write_record(&header)?;
+
+        let mut heap: MinMaxHeap<HeapEntry> = MinMaxHeap::with_capacity(readers.len());
+        for (shard, reader) in readers.iter_mut().enumerate() {
+            if let Some(row) = next_row(reader)? {
+                heap.push(HeapEntry {
+                    doi: row.get(0).unwrap_or("").to_string(),
+                    row,
+                    shard,
+                });
+            }
+        }
+
+        while let Some(entry) = heap.pop_min() {
+            writer.write_record(&entry.row)?;
+            if let Some(row) = next_row(&mut readers[entry.shard])? {
+                heap.push(HeapEntry {
+                    doi: row.get(0).unwrap_or("").to_string(),
+                    row,
+                    shard: entry.shard,
+                });
+            }
+        }
+
+        writer.flush()?;
+        for path in shard_paths {
+            let _ = std::fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    fn next_row(
+        reader: &mut csv::Reader<Decoder<BufReader<File>>>,
+    ) -> Result<Option<csv::StringRecord>, AppError> {
+        let mut record = csv::StringRecord::new();
+        if reader.read_record(&mut record)? {
+            Ok(Some(record))
         } else {
-            #[cfg(target_os = "linux")]
-            info!("Failed to get memory usage on Linux");
-            #[cfg(target_os = "macos")]
-            info!("Failed to get memory usage on macOS");
-            #[cfg(target_os = "windows")]
-            info!("Failed to get memory usage on Windows");
-            #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-            info!("Memory usage tracking not available on this platform");
+            Ok(None)
         }
     }
 }
@@ -205,12 +1162,18 @@ enum AppError {
     Io(#[from] std::io::Error),
     #[error("JSON Error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Parquet Error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Arrow Error: {0}")]
+    Arrow(#[from] arrow::error::ArrowError),
+    #[error("Tantivy Error: {0}")]
+    Tantivy(#[from] tantivy::TantivyError),
     #[error("Directory Traversal Error: {0}")]
     WalkDir(#[from] walkdir::Error),
     #[error("Mutex was poisoned (likely due to panic in another thread)")]
     MutexPoisoned,
     #[error("Channel send error: {0}")]
-    SendError(#[from] mpsc::SendError<Option<Vec<MatchResult>>>),
+    SendError(#[from] crossbeam_channel::SendError<Option<Vec<MatchResult>>>),
     #[error("No input DOIs found in the mapping CSV")]
     NoInputDois,
     #[error("Output file path is invalid")]
@@ -220,9 +1183,11 @@ enum AppError {
     #[error("Input mapping file not found: {0}")]
     MappingFileNotFound(String),
     #[error("Processing failed for file {0}: {1}")]
-    FileProcessingFailed(PathBuf, String),
+    FileProcessingFailed(String, String),
     #[error("Progress bar template error: {0}")]
     Template(#[from] TemplateError),
+    #[error("Invalid --partition-filter: {0}")]
+    InvalidPartitionFilter(String),
 }
 
 impl<T> From<std::sync::PoisonError<T>> for AppError {
@@ -231,18 +1196,53 @@ impl<T> From<std::sync::PoisonError<T>> for AppError {
     }
 }
 
+/// Machine-readable mirror of the "Completion Summary" log lines, written
+/// to `--summary-json` (or stderr) so batch-processing harnesses can parse
+/// run outcomes without scraping log text.
+#[derive(Serialize, Debug)]
+struct CompletionSummary {
+    files_processed_ok: usize,
+    files_total: usize,
+    file_errors: usize,
+    total_matches_found: u64,
+    elapsed_seconds: f64,
+    peak_memory_mb: f64,
+    exit_code: i32,
+}
+
+/// Process exit codes for scripting/CI: a clean run, a run that completed
+/// but hit per-file errors, and a run that couldn't complete at all
+/// (writer-thread failure or an error propagated via `?` out of `main`).
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_PARTIAL_FAILURE: i32 = 2;
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(short = 'm', long = "mapping-csv", required = true)]
     mapping_csv: PathBuf,
-    #[arg(short = 'i', long = "input-dir", required = true)]
-    input_dir: PathBuf,
+    #[arg(
+        short = 'i',
+        long = "input-dir",
+        required = true,
+        help = "Local directory, or an s3://, gs://, az://, https:// URI to scan for input files"
+    )]
+    input_dir: String,
     #[arg(short = 'o', long = "output-csv", required = true)]
     output_csv: PathBuf,
-    #[arg(short = 't', long = "threads", default_value_t = 0)]
+    #[arg(
+        short = 't',
+        long = "threads",
+        default_value_t = 0,
+        help = "Number of matcher threads; 0 auto-tunes from logical CPU count (min(cpus, 8))"
+    )]
     threads: usize,
-    #[arg(short = 'b', long = "batch-size", default_value_t = 10000)]
+    #[arg(
+        short = 'b',
+        long = "batch-size",
+        default_value_t = 0,
+        help = "Base records per batch sent to the writer, scaled by total system memory against an 8 GiB baseline; 0 auto-tunes from available system memory"
+    )]
     batch_size: usize,
     #[arg(long = "log-level", default_value = "INFO", value_parser = clap::value_parser!(LevelFilter))]
     log_level: LevelFilter,
@@ -254,6 +1254,54 @@ struct Args {
         value_delimiter = ','
     )]
     relation_types: Option<Vec<String>>,
+    #[arg(
+        long = "partition-filter",
+        help = "Prune Hive-style 'key=value' partitioned input files before they're opened, e.g. \"created>=2022,client=foo\""
+    )]
+    partition_filter: Option<String>,
+    #[arg(
+        long = "mem-high",
+        default_value_t = 0.85,
+        help = "Fraction of system memory used above which in-flight batches are force-flushed"
+    )]
+    mem_high: f64,
+    #[arg(
+        long = "mem-low",
+        default_value_t = 0.70,
+        help = "Fraction of system memory used below which normal batching resumes"
+    )]
+    mem_low: f64,
+    #[arg(
+        long = "max-open-files",
+        help = "Ceiling for the raised RLIMIT_NOFILE soft limit; defaults to the hard limit (and, on macOS, kern.maxfilesperproc)"
+    )]
+    max_open_files: Option<u64>,
+    #[arg(
+        long = "output-format",
+        help = "Output sink format; defaults to inferring from --output-csv's extension (.ndjson/.jsonl, .parquet/.parq, .tantivy, else CSV)"
+    )]
+    output_format: Option<OutputFormat>,
+    #[arg(
+        long = "channel-capacity",
+        default_value_t = 0,
+        help = "Bounded channel capacity (in batches) between workers and the output writer thread; 0 auto-sizes to 4x the auto-tuned thread count (min(cpus, 8))"
+    )]
+    channel_capacity: usize,
+    #[arg(
+        long = "sharded-output",
+        help = "Skip the single writer thread: each worker sorts and LZ4-compresses its own shard, then shards are k-way merged into one DOI-sorted CSV at --output-csv. Ignores --output-format."
+    )]
+    sharded_output: bool,
+    #[arg(
+        long = "single-threaded",
+        help = "Force deterministic single-threaded execution: files are matched and written in order with no writer thread or channel. Always on if built without the `threads` feature."
+    )]
+    single_threaded: bool,
+    #[arg(
+        long = "summary-json",
+        help = "Write the machine-readable completion summary as JSON to this path instead of stderr"
+    )]
+    summary_json: Option<PathBuf>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -304,6 +1352,7 @@ struct MatchResult {
     datacite_record_doi: Option<String>,
     datacite_record_resource_type: Option<String>,
     datacite_record_resource_type_general: Option<String>,
+    partition_values: partition::PartitionValues,
 }
 
 fn normalize_doi(doi_str: &str) -> String {
@@ -401,44 +1450,27 @@ fn load_input_dois(csv_path: &Path) -> Result<HashSet<String>, AppError> {
 }
 
 fn process_datacite_file_content(
-    file_path: &Path,
+    store: &dyn store::Store,
+    key: &store::InputKey,
+    partition_values: &partition::PartitionValues,
     input_dois: Arc<HashSet<String>>,
     relation_type_filter: Arc<Option<HashSet<String>>>,
 ) -> Result<Vec<MatchResult>, String>
 {
     let mut file_matches = Vec::new();
+    let display_name = key.display();
 
-    let file = File::open(file_path).map_err(|e| e.to_string())?;
-    let gz = GzDecoder::new(file);
-    let reader = BufReader::new(gz);
+    let mut reader = store.open(key).map_err(|e| e.to_string())?;
+    let shape = input_format::RecordShape::detect(&display_name, reader.as_mut())
+        .map_err(|e| e.to_string())?;
 
-    for (line_num, line_result) in reader.lines().enumerate() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(e) => {
-                warn!(
-                    "Error reading line {} in {}: {}. Skipping line.",
-                    line_num + 1,
-                    file_path.display(),
-                    e
-                );
-                continue;
-            }
-        };
-
-        if line.trim().is_empty() {
-            continue;
-        }
-
-        let record: DataCiteRecord = match serde_json::from_str(&line) {
+    for (record_num, record_result) in shape.read_records(reader) {
+        let record: DataCiteRecord = match record_result {
             Ok(r) => r,
             Err(e) => {
                 warn!(
-                    "JSON parse error on line {} in {}: {} (Line: {}...). Skipping line.",
-                    line_num + 1,
-                    file_path.display(),
-                    e,
-                    line.chars().take(100).collect::<String>()
+                    "JSON parse error on record {} in {}: {}. Skipping record.",
+                    record_num, display_name, e
                 );
                 continue;
             }
@@ -485,6 +1517,7 @@ fn process_datacite_file_content(
                             datacite_record_resource_type_general: record_types
                                 .as_ref()
                                 .and_then(|t| t.resource_type_general.clone()),
+                            partition_values: partition_values.clone(),
                         };
                         file_matches.push(result);
                     }
@@ -495,6 +1528,302 @@ fn process_datacite_file_content(
     Ok(file_matches)
 }
 
+/// Batches the writer thread hasn't drained yet, analogous to a CSV
+/// decoder's `capacity()`: lets a worker check whether the writer is
+/// saturated before it goes on to accumulate (and block on sending)
+/// another batch.
+fn writer_remaining_capacity(tx: &Sender<Option<Vec<MatchResult>>>) -> usize {
+    match tx.capacity() {
+        Some(capacity) => capacity.saturating_sub(tx.len()),
+        None => usize::MAX,
+    }
+}
+
+/// Deterministic single-threaded fallback used by `--single-threaded` and
+/// whenever the crate is built without the `threads` feature: matches are
+/// read and written strictly in file order, with no writer thread or
+/// channel. Useful for reproducing bugs, stable output ordering in tests,
+/// and sandboxed environments where spawning threads is undesirable.
+mod serial {
+    use super::*;
+
+    pub fn run(
+        files_to_process: &[(store::InputKey, partition::PartitionValues)],
+        input_store: &dyn store::Store,
+        input_dois: &Arc<HashSet<String>>,
+        relation_type_filter: &Arc<Option<HashSet<String>>>,
+        progress_bar: &ProgressBar,
+        output_path: &Path,
+        output_format: OutputFormat,
+        partition_columns: &[String],
+    ) -> Result<Vec<Result<usize, AppError>>, AppError> {
+        let mut sink = sink::build(output_path, Some(output_format), partition_columns.to_vec())?;
+        let mut results = Vec::with_capacity(files_to_process.len());
+
+        for (key, partition_values) in files_to_process {
+            progress_bar.set_message(format!("Processing: {}", key.display()));
+
+            match process_datacite_file_content(
+                input_store,
+                key,
+                partition_values,
+                Arc::clone(input_dois),
+                Arc::clone(relation_type_filter),
+            ) {
+                Ok(file_matches) => {
+                    let matches_count = file_matches.len();
+                    if !file_matches.is_empty() {
+                        sink.write_batch(&file_matches)?;
+                    }
+                    progress_bar.inc(1);
+                    results.push(Ok(matches_count));
+                }
+                Err(e) => {
+                    error!("Error processing file {}: {}", key.display(), e);
+                    progress_bar.inc(1);
+                    results.push(Err(AppError::FileProcessingFailed(key.display(), e)));
+                }
+            }
+        }
+
+        sink.finish()?;
+        Ok(results)
+    }
+}
+
+#[cfg(feature = "threads")]
+fn run_threaded(
+    args: &Args,
+    files_to_process: &[(store::InputKey, partition::PartitionValues)],
+    input_store: &dyn store::Store,
+    input_dois: &Arc<HashSet<String>>,
+    relation_type_filter: &Arc<Option<HashSet<String>>>,
+    progress_bar: &ProgressBar,
+    process_start_time: Instant,
+    partition_columns: &[String],
+    effective_batch_size: usize,
+    memory_monitor: &memory_usage::MemoryMonitor,
+    tuned: autotune::TunedParams,
+    resolved_output_format: OutputFormat,
+) -> Result<Vec<Result<usize, AppError>>, AppError> {
+    if args.sharded_output {
+        let shard_dir = tempfile::tempdir()?;
+        let num_shards = rayon::current_num_threads().max(1);
+        info!(
+            "Sharded output: {} worker shard(s) staged in {}",
+            num_shards,
+            shard_dir.path().display()
+        );
+        let shards: Vec<Mutex<sharded::Shard>> = (0..num_shards)
+            .map(|i| Mutex::new(sharded::Shard::new(shard_dir.path(), i)))
+            .collect();
+
+        let results: Vec<Result<usize, AppError>> = files_to_process
+            .par_iter()
+            .map(|(key, partition_values)| {
+                progress_bar.set_message(format!("Processing: {}", key.display()));
+
+                let input_dois_clone = Arc::clone(input_dois);
+                let relation_filter_clone = Arc::clone(relation_type_filter);
+
+                match process_datacite_file_content(
+                    input_store,
+                    key,
+                    partition_values,
+                    input_dois_clone,
+                    relation_filter_clone,
+                ) {
+                    Ok(file_matches) => {
+                        let matches_count = file_matches.len();
+                        if !file_matches.is_empty() {
+                            let shard_index = rayon::current_thread_index().unwrap_or(0) % num_shards;
+                            shards[shard_index].lock()?.extend(file_matches);
+                        }
+                        progress_bar.inc(1);
+                        Ok(matches_count)
+                    }
+                    Err(e) => {
+                        error!("Error processing file {}: {}", key.display(), e);
+                        progress_bar.inc(1);
+                        Err(AppError::FileProcessingFailed(key.display(), e))
+                    }
+                }
+            })
+            .collect();
+
+        info!("Parallel processing loop finished in {}.", format_elapsed(process_start_time.elapsed()));
+        info!("Sorting and compressing {} shard(s)...", num_shards);
+        let shard_paths: Vec<PathBuf> = shards
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, shard)| {
+                let shard = shard.into_inner().map_err(|_| AppError::MutexPoisoned).ok()?;
+                match shard.finish(&partition_columns) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        error!("Failed to flush shard {}: {}", i, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        info!(
+            "K-way merging {} non-empty shard(s) into {}",
+            shard_paths.len(),
+            args.output_csv.display()
+        );
+        sharded::merge(&shard_paths, &args.output_csv, &partition_columns)?;
+
+        results
+    } else {
+        let channel_capacity = if args.channel_capacity > 0 {
+            args.channel_capacity
+        } else {
+            tuned.channel_capacity
+        };
+        info!(
+            "Using a bounded channel with capacity: {} batches",
+            channel_capacity
+        );
+        let (tx, rx) = bounded::<Option<Vec<MatchResult>>>(channel_capacity);
+        let batch_collector = Arc::new(Mutex::new(Vec::with_capacity(effective_batch_size)));
+
+        let output_csv_path = args.output_csv.clone();
+        let writer_partition_columns = partition_columns.to_vec();
+        let output_format = Some(resolved_output_format);
+        let csv_writer_thread = std::thread::spawn(move || -> Result<(), AppError> {
+            info!("Output writer thread started.");
+            let mut sink = sink::build(&output_csv_path, output_format, writer_partition_columns)?;
+
+            while let Ok(batch_option) = rx.recv() {
+                match batch_option {
+                    Some(batch) => {
+                        if batch.is_empty() { continue; }
+                        sink.write_batch(&batch)?;
+                    }
+                    None => {
+                        info!("Output writer received None (termination signal). Flushing and exiting.");
+                        break;
+                    }
+                }
+            }
+            sink.finish()?;
+            info!("Output writer thread finished.");
+            Ok(())
+        });
+
+        let results: Vec<Result<usize, AppError>> = files_to_process
+            .par_iter()
+            .map(|(key, partition_values)| {
+                progress_bar.set_message(format!("Processing: {}", key.display()));
+
+                let input_dois_clone = Arc::clone(input_dois);
+                let relation_filter_clone = Arc::clone(relation_type_filter);
+
+                match process_datacite_file_content(
+                    input_store,
+                    key,
+                    partition_values,
+                    input_dois_clone,
+                    relation_filter_clone,
+                ) {
+                    Ok(file_matches) => {
+                        let matches_count = file_matches.len();
+
+                        if !file_matches.is_empty() {
+                            let mut batch_guard = batch_collector.lock()?;
+                            batch_guard.extend(file_matches);
+
+                            if batch_guard.len() >= effective_batch_size || memory_monitor.should_flush() {
+                                let batch_to_send =
+                                    std::mem::replace(&mut *batch_guard, Vec::with_capacity(effective_batch_size));
+                                drop(batch_guard);
+
+                                if writer_remaining_capacity(&tx) == 0 {
+                                    warn!(
+                                        "Output writer channel is saturated; worker will block until it catches up"
+                                    );
+                                }
+                                tx.send(Some(batch_to_send)).map_err(|e| {
+                                    error!("Fatal: Failed to send batch to CSV writer: {}", e);
+                                    AppError::SendError(e)
+                                })?;
+                            }
+                        }
+                        progress_bar.inc(1);
+                        Ok(matches_count)
+                    }
+                    Err(e) => {
+                        error!(
+                            "Error processing file {}: {}",
+                            key.display(),
+                            e
+                        );
+                        progress_bar.inc(1);
+                        Err(AppError::FileProcessingFailed(key.display(), e))
+                    }
+                }
+            })
+            .collect();
+
+        info!("Parallel processing loop finished in {}.", format_elapsed(process_start_time.elapsed()));
+
+        info!("Sending final batch if any...");
+        let final_batch = {
+            let mut batch_guard = batch_collector.lock()?;
+            std::mem::replace(&mut *batch_guard, Vec::new())
+        };
+
+        if !final_batch.is_empty() {
+            info!("Sending final batch of size {}.", final_batch.len());
+             tx.send(Some(final_batch)).map_err(|e| {
+                error!("Fatal: Failed to send final batch to CSV writer: {}", e);
+                AppError::SendError(e)
+            })?;
+        }
+
+        info!("Signaling CSV writer thread to terminate...");
+         tx.send(None).map_err(|e| {
+            error!("Fatal: Failed to send termination signal to CSV writer: {}", e);
+            AppError::SendError(e)
+        })?;
+
+        info!("Waiting for CSV writer thread to join...");
+        match csv_writer_thread.join() {
+            Ok(Ok(())) => info!("CSV writer thread joined successfully."),
+            Ok(Err(e)) => {
+                error!("CSV writer thread returned an error: {}", e);
+                 return Err(e);
+            }
+            Err(e) => {
+                error!("Failed to join CSV writer thread (panic): {:?}", e);
+                 return Err(AppError::MutexPoisoned);
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(not(feature = "threads"))]
+fn run_threaded(
+    _args: &Args,
+    _files_to_process: &[(store::InputKey, partition::PartitionValues)],
+    _input_store: &dyn store::Store,
+    _input_dois: &Arc<HashSet<String>>,
+    _relation_type_filter: &Arc<Option<HashSet<String>>>,
+    _progress_bar: &ProgressBar,
+    _process_start_time: Instant,
+    _partition_columns: &[String],
+    _effective_batch_size: usize,
+    _memory_monitor: &memory_usage::MemoryMonitor,
+    _tuned: autotune::TunedParams,
+    _resolved_output_format: OutputFormat,
+) -> Result<Vec<Result<usize, AppError>>, AppError> {
+    unreachable!("built without the `threads` feature; --single-threaded (or the feature being off) must force the serial path")
+}
+
 fn main() -> Result<(), AppError> {
     let main_start_time = Instant::now();
     let args = Args::parse();
@@ -505,18 +1834,22 @@ fn main() -> Result<(), AppError> {
         .init();
 
     info!("Starting DataCite DOI Matcher V2.1 (Relation Type Filter)");
+    fd_limit::raise_nofile_limit(args.max_open_files);
     memory_usage::log_memory_usage("startup");
 
     info!("Configuration:");
     info!("  Mapping CSV: {}", args.mapping_csv.display());
-    info!("  Input Directory: {}", args.input_dir.display());
+    info!("  Input Directory: {}", args.input_dir);
     info!("  Output CSV: {}", args.output_csv.display());
     info!("  Threads: {}", if args.threads == 0 { "Auto".to_string() } else { args.threads.to_string() });
     info!("  Batch Size: {}", args.batch_size);
     info!("  Log Level: {}", args.log_level);
+    if let Some(filter) = args.partition_filter.as_deref() {
+        info!("  Partition Filter: {}", filter);
+    }
 
-    let relation_type_filter = Arc::new(args.relation_types.map(|types| {
-        let filter_set: HashSet<String> = types.into_iter().collect();
+    let relation_type_filter = Arc::new(args.relation_types.as_ref().map(|types| {
+        let filter_set: HashSet<String> = types.iter().cloned().collect();
         info!("  Filtering for Relation Types: {:?}", filter_set);
         filter_set
     }));
@@ -525,10 +1858,12 @@ fn main() -> Result<(), AppError> {
     }
 
 
-    if !args.input_dir.is_dir() {
-        return Err(AppError::InputDirectoryNotFound(
-            args.input_dir.display().to_string(),
-        ));
+    let is_remote_input = args.input_dir.contains("://") && !args.input_dir.starts_with("file://");
+    if !is_remote_input {
+        let local_root = args.input_dir.strip_prefix("file://").unwrap_or(&args.input_dir);
+        if !Path::new(local_root).is_dir() {
+            return Err(AppError::InputDirectoryNotFound(args.input_dir.clone()));
+        }
     }
     if let Some(parent) = args.output_csv.parent() {
         if !parent.exists() {
@@ -541,41 +1876,84 @@ fn main() -> Result<(), AppError> {
     let input_dois = Arc::new(load_input_dois(&args.mapping_csv)?);
     info!("Loaded input DOIs in {}", format_elapsed(load_start.elapsed()));
 
-    if args.threads > 0 {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(args.threads)
-            .build_global()
-            .expect("Failed to build Rayon thread pool");
-        info!("Using {} worker threads.", args.threads);
+    let tuned = autotune::recommend();
+
+    let matcher_threads = if args.threads > 0 { args.threads } else { tuned.threads };
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(matcher_threads)
+        .build_global()
+        .expect("Failed to build Rayon thread pool");
+    info!("Using {} worker threads.", matcher_threads);
+
+    let effective_batch_size = if args.batch_size > 0 {
+        match memory_usage::get_memory_usage() {
+            Some(stats) => {
+                let scaled = memory_usage::adaptive_batch_size(args.batch_size, stats.total_mb);
+                info!(
+                    "Adaptive batch size: {} (base {}, {:.0} MB total system memory)",
+                    scaled, args.batch_size, stats.total_mb
+                );
+                scaled
+            }
+            None => args.batch_size,
+        }
     } else {
-        info!("Using default number of Rayon threads.");
-    }
+        tuned.batch_size
+    };
+    let memory_monitor = memory_usage::MemoryMonitor::start(args.mem_high, args.mem_low, Duration::from_secs(2));
+
+    let partition_predicates = args
+        .partition_filter
+        .as_deref()
+        .map(partition::parse_filter)
+        .transpose()
+        .map_err(AppError::InvalidPartitionFilter)?;
 
     let find_start = Instant::now();
-    let files_to_process: Vec<PathBuf> = WalkDir::new(&args.input_dir)
+    let input_store = store::for_input_dir(&args.input_dir)
+        .map_err(|e| AppError::InputDirectoryNotFound(format!("{}: {}", args.input_dir, e)))?;
+    let discovered: Vec<store::InputKey> = input_store
+        .list()
+        .map_err(|e| AppError::InputDirectoryNotFound(format!("{}: {}", args.input_dir, e)))?;
+
+    let files_to_process: Vec<(store::InputKey, partition::PartitionValues)> = discovered
         .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "gz"))
-        .filter(|e| {
-            e.path()
-                .file_stem()
-                .map_or(false, |stem| stem.to_string_lossy().ends_with(".jsonl"))
+        .map(|key| {
+            let values = partition::parse(&key.display());
+            (key, values)
+        })
+        .filter(|(_, values)| {
+            partition_predicates
+                .as_ref()
+                .map_or(true, |predicates| partition::matches(predicates, values))
         })
-        .map(|e| e.into_path())
         .collect();
 
     if files_to_process.is_empty() {
-        warn!("No '.jsonl.gz' files found in {}. Exiting.", args.input_dir.display());
+        warn!(
+            "No supported DataCite files (.jsonl.gz, .jsonl.bz2, .jsonl.zst, .jsonl, .json) found in {} matching the partition filter. Exiting.",
+            args.input_dir
+        );
         return Ok(());
     }
     let total_files = files_to_process.len();
     info!(
-        "Found {} '.jsonl.gz' files to process in {}",
+        "Found {} supported DataCite file(s) to process in {}",
         total_files,
         format_elapsed(find_start.elapsed())
     );
 
+    let partition_columns: Vec<String> = files_to_process
+        .iter()
+        .flat_map(|(_, values)| values.keys().cloned())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let resolved_output_format = args
+        .output_format
+        .unwrap_or_else(|| OutputFormat::from_extension(&args.output_csv));
+
     let progress_bar = ProgressBar::new(total_files as u64);
     progress_bar.set_style(
         ProgressStyle::default_bar()
@@ -586,139 +1964,42 @@ fn main() -> Result<(), AppError> {
     );
     progress_bar.set_message("Starting processing...");
 
-    let (tx, rx) = mpsc::channel::<Option<Vec<MatchResult>>>();
-    let batch_collector = Arc::new(Mutex::new(Vec::with_capacity(args.batch_size)));
-
-    let output_csv_path = args.output_csv.clone();
-    let csv_writer_thread = std::thread::spawn(move || -> Result<(), AppError> {
-        info!("CSV writer thread started.");
-        let output_file = BufWriter::new(File::create(&output_csv_path)?);
-        let mut writer = WriterBuilder::new()
-            .quote_style(csv::QuoteStyle::Necessary)
-            .from_writer(output_file);
-
-        writer.write_record(&[
-            "input_doi",
-            "datacite_record_doi",
-            "matched_relation_type",
-            "datacite_record_resource_type",
-            "datacite_record_resource_type_general",
-        ])?;
-        writer.flush()?;
-
-        while let Ok(batch_option) = rx.recv() {
-            match batch_option {
-                Some(batch) => {
-                    if batch.is_empty() { continue; }
-                    for result in batch {
-                        writer.write_record(&[
-                            &result.input_doi,
-                            result.datacite_record_doi.as_deref().unwrap_or(""),
-                            result.matched_relation_type.as_deref().unwrap_or(""),
-                            result
-                                .datacite_record_resource_type
-                                .as_deref()
-                                .unwrap_or(""),
-                            result
-                                .datacite_record_resource_type_general
-                                .as_deref()
-                                .unwrap_or(""),
-                        ])?;
-                    }
-                    writer.flush()?;
-                }
-                None => {
-                    info!("CSV writer received None (termination signal). Flushing and exiting.");
-                    break;
-                }
-            }
-        }
-        writer.flush()?;
-        info!("CSV writer thread finished.");
-        Ok(())
-    });
+    let single_threaded = args.single_threaded || !cfg!(feature = "threads");
 
     let process_start_time = Instant::now();
-    info!("Starting parallel file processing...");
-
-    let processing_results: Vec<Result<usize, AppError>> = files_to_process
-        .par_iter()
-        .map(|file_path| {
-            let file_name_for_msg = file_path.file_name().map_or_else(|| file_path.to_string_lossy(), |n| n.to_string_lossy());
-            progress_bar.set_message(format!("Processing: {}", file_name_for_msg));
-
-            let input_dois_clone = Arc::clone(&input_dois);
-            let relation_filter_clone = Arc::clone(&relation_type_filter);
-
-            match process_datacite_file_content(file_path, input_dois_clone, relation_filter_clone) {
-                Ok(file_matches) => {
-                    let matches_count = file_matches.len();
-
-                    if !file_matches.is_empty() {
-                        let mut batch_guard = batch_collector.lock()?;
-                        batch_guard.extend(file_matches);
-
-                        if batch_guard.len() >= args.batch_size {
-                            let batch_to_send =
-                                std::mem::replace(&mut *batch_guard, Vec::with_capacity(args.batch_size));
-                            drop(batch_guard);
-
-                            tx.send(Some(batch_to_send)).map_err(|e| {
-                                error!("Fatal: Failed to send batch to CSV writer: {}", e);
-                                AppError::SendError(e)
-                            })?;
-                        }
-                    }
-                    progress_bar.inc(1);
-                    Ok(matches_count)
-                }
-                Err(e) => {
-                    error!(
-                        "Error processing file {}: {}",
-                        file_path.display(),
-                        e
-                    );
-                    progress_bar.inc(1);
-                    Err(AppError::FileProcessingFailed(file_path.clone(), e))
-                }
-            }
-        })
-        .collect();
-
-    info!("Parallel processing loop finished in {}.", format_elapsed(process_start_time.elapsed()));
-
-    info!("Sending final batch if any...");
-    let final_batch = {
-        let mut batch_guard = batch_collector.lock()?;
-        std::mem::replace(&mut *batch_guard, Vec::new())
-    };
-
-    if !final_batch.is_empty() {
-        info!("Sending final batch of size {}.", final_batch.len());
-         tx.send(Some(final_batch)).map_err(|e| {
-            error!("Fatal: Failed to send final batch to CSV writer: {}", e);
-            AppError::SendError(e)
-        })?;
+    if single_threaded {
+        info!("Starting single-threaded file processing...");
+    } else {
+        info!("Starting parallel file processing...");
     }
 
-    info!("Signaling CSV writer thread to terminate...");
-     tx.send(None).map_err(|e| {
-        error!("Fatal: Failed to send termination signal to CSV writer: {}", e);
-        AppError::SendError(e)
-    })?;
-
-    info!("Waiting for CSV writer thread to join...");
-    match csv_writer_thread.join() {
-        Ok(Ok(())) => info!("CSV writer thread joined successfully."),
-        Ok(Err(e)) => {
-            error!("CSV writer thread returned an error: {}", e);
-             return Err(e);
-        }
-        Err(e) => {
-            error!("Failed to join CSV writer thread (panic): {:?}", e);
-             return Err(AppError::MutexPoisoned);
-        }
-    }
+    let processing_results: Vec<Result<usize, AppError>> = if single_threaded {
+        serial::run(
+            &files_to_process,
+            input_store.as_ref(),
+            &input_dois,
+            &relation_type_filter,
+            &progress_bar,
+            &args.output_csv,
+            resolved_output_format,
+            &partition_columns,
+        )?
+    } else {
+        run_threaded(
+            &args,
+            &files_to_process,
+            input_store.as_ref(),
+            &input_dois,
+            &relation_type_filter,
+            &progress_bar,
+            process_start_time,
+            &partition_columns,
+            effective_batch_size,
+            &memory_monitor,
+            tuned,
+            resolved_output_format,
+        )?
+    };
 
     progress_bar.finish_with_message(format!(
         "Processing finished in {}",
@@ -743,6 +2024,9 @@ fn main() -> Result<(), AppError> {
     info!("--- Completion Summary ---");
     info!("  Processed {} / {} input files successfully.", files_processed_ok, total_files);
     info!("  Total matches found: {}", total_matches_found);
+    if !args.sharded_output && resolved_output_format == OutputFormat::Tantivy {
+        info!("  Full-text search index: {}", args.output_csv.display());
+    }
     info!("  Total execution time: {}", format_elapsed(main_start_time.elapsed()));
 
     if file_errors > 0 {
@@ -761,5 +2045,26 @@ fn main() -> Result<(), AppError> {
         format_elapsed(main_start_time.elapsed())
     );
 
-    Ok(())
+    let exit_code = if file_errors > 0 {
+        EXIT_PARTIAL_FAILURE
+    } else {
+        EXIT_SUCCESS
+    };
+
+    let summary = CompletionSummary {
+        files_processed_ok,
+        files_total: total_files,
+        file_errors,
+        total_matches_found,
+        elapsed_seconds: main_start_time.elapsed().as_secs_f64(),
+        peak_memory_mb: memory_monitor.peak_used_mb(),
+        exit_code,
+    };
+    let summary_json = serde_json::to_string(&summary)?;
+    match &args.summary_json {
+        Some(path) => std::fs::write(path, summary_json)?,
+        None => eprintln!("{}", summary_json),
+    }
+
+    std::process::exit(exit_code);
 }
\ No newline at end of file